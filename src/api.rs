@@ -0,0 +1,58 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Errors of the requests to the APIs of the platforms and instance lists.
+
+#[derive(Debug)]
+pub enum Error {
+    Network(reqwest::Error),
+    ParseResponse(serde_json::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Network(e) => write!(f, "{}", e),
+            Error::ParseResponse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Network(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::ParseResponse(e)
+    }
+}
+
+impl From<&Error> for tf_core::Error {
+    fn from(e: &Error) -> Self {
+        match e {
+            Error::Network(e) => tf_core::NetworkError(e.to_string()).into(),
+            Error::ParseResponse(e) => tf_core::ParseError(e.to_string()).into(),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,407 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The commands of the player and downloader.
+//!
+//! Commands are split into arguments like a shell would, so arguments may be quoted. They may
+//! contain placeholders anywhere, which are replaced by the values of the video without splitting
+//! them again.
+//!
+//! On Flatpak, the programs are run outside of the sandbox, where the players are installed.
+
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// The number of lines at the end of the standard error kept to report a failure.
+const STDERR_LINES: usize = 20;
+
+/// The exit code of `flatpak-spawn` if the program is not installed on the host, as reported by
+/// its shell.
+const NOT_FOUND_EXIT_CODE: i32 = 127;
+
+static PLACEHOLDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{([a-z_]+)\}").expect("Placeholder regex to be valid"));
+
+/// The placeholders which may be used in commands.
+pub const PLACEHOLDERS: [&str; 5] = ["url", "title", "author", "platform", "local_path"];
+
+/// The values replacing the placeholders of a command.
+#[derive(Debug, Clone, Default)]
+pub struct Placeholders {
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub platform: Option<String>,
+    pub local_path: Option<String>,
+}
+
+impl Placeholders {
+    /// Only the url, e.g. for a playlist.
+    pub fn url(url: String) -> Self {
+        Self {
+            url: Some(url),
+            ..Default::default()
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        match name {
+            "url" => self.url.as_deref(),
+            "title" => self.title.as_deref(),
+            "author" => self.author.as_deref(),
+            "platform" => self.platform.as_deref(),
+            "local_path" => self.local_path.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The command could not be split into arguments, e.g. because of an unclosed quote.
+    Parse(shell_words::ParseError),
+    Empty,
+    UnknownPlaceholder(String),
+    /// The program could not be run.
+    Run(String, std::io::Error),
+    /// The program exited unsuccessfully with the exit code, if any, and the end of its standard
+    /// error.
+    Exit(String, Option<i32>, String),
+}
+
+impl Error {
+    /// The program which could not be run because it is not installed.
+    pub fn missing_program(&self) -> Option<&str> {
+        self.missing_program_on(crate::config::FLATPAK)
+    }
+
+    /// The program which could not be run because it is not installed, on Flatpak or not.
+    ///
+    /// On Flatpak, `flatpak-spawn` is run instead of the program, which reports a missing program
+    /// only by its exit code.
+    fn missing_program_on(&self, flatpak: bool) -> Option<&str> {
+        match self {
+            Error::Run(program, e) if e.kind() == std::io::ErrorKind::NotFound => Some(program),
+            Error::Exit(program, Some(NOT_FOUND_EXIT_CODE), _) if flatpak => Some(program),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::Empty => write!(f, "{}", gettextrs::gettext("The command is empty")),
+            Error::UnknownPlaceholder(p) => {
+                let placeholders: Vec<String> =
+                    PLACEHOLDERS.iter().map(|p| format!("{{{}}}", p)).collect();
+                write!(
+                    f,
+                    "{}",
+                    gettextrs::gettext(
+                        "Unknown placeholder {placeholder}, use one of {placeholders}"
+                    )
+                    .replace("{placeholders}", &placeholders.join(", "))
+                    .replace("{placeholder}", &format!("{{{}}}", p))
+                )
+            }
+            Error::Run(program, _) | Error::Exit(program, ..)
+                if self.missing_program().is_some() =>
+            {
+                write!(
+                    f,
+                    "{}",
+                    gettextrs::gettext("The program {} was not found, is it installed?")
+                        .replace("{}", program)
+                )
+            }
+            Error::Run(program, e) => write!(f, "{}: {}", program, e),
+            Error::Exit(program, code, stderr) => {
+                let message = match code {
+                    Some(code) => gettextrs::gettext("{program} exited with code {code}")
+                        .replace("{program}", program)
+                        .replace("{code}", &code.to_string()),
+                    None => {
+                        gettextrs::gettext("{program} was terminated").replace("{program}", program)
+                    }
+                };
+                if stderr.is_empty() {
+                    write!(f, "{}", message)
+                } else {
+                    write!(f, "{}\n\n{}", message, stderr)
+                }
+            }
+        }
+    }
+}
+
+impl From<shell_words::ParseError> for Error {
+    fn from(e: shell_words::ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+/// Split the command into arguments and check its placeholders.
+pub fn parse(command: &str) -> Result<Vec<String>, Error> {
+    let args = shell_words::split(command)?;
+    if args.is_empty() {
+        return Err(Error::Empty);
+    }
+    for captures in args.iter().flat_map(|a| PLACEHOLDER.captures_iter(a)) {
+        let name = &captures[1];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(Error::UnknownPlaceholder(name.to_owned()));
+        }
+    }
+    Ok(args)
+}
+
+/// The arguments to run, starting with the program.
+///
+/// Placeholders without a value are replaced by nothing, arguments consisting only of such
+/// placeholders are dropped. If neither `{url}` nor `{local_path}` is used with a value, e.g. as
+/// the video was not downloaded, the local path or else the url is appended.
+pub fn build(command: &str, values: &Placeholders) -> Result<Vec<String>, Error> {
+    let args = parse(command)?;
+    let mut uses_target = false;
+    let mut result: Vec<String> = args
+        .iter()
+        .filter_map(|arg| {
+            let mut has_placeholder = false;
+            let replaced = PLACEHOLDER.replace_all(arg, |captures: &regex::Captures| {
+                has_placeholder = true;
+                let name = &captures[1];
+                let value = values.get(name);
+                uses_target |= (name == "url" || name == "local_path") && value.is_some();
+                value.unwrap_or_default().to_owned()
+            });
+            if has_placeholder && replaced.is_empty() {
+                None
+            } else {
+                Some(replaced.into_owned())
+            }
+        })
+        .collect();
+    if !uses_target {
+        if let Some(target) = values.local_path.as_ref().or(values.url.as_ref()) {
+            result.push(target.clone());
+        }
+    }
+    Ok(result)
+}
+
+/// The command running the arguments, the first argument being the program.
+fn host_command(args: &[String]) -> Command {
+    if crate::config::FLATPAK {
+        let mut command = Command::new("flatpak-spawn");
+        command.arg("--host").args(args);
+        command
+    } else {
+        let mut command = Command::new(&args[0]);
+        command.args(&args[1..]);
+        command
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or_default()
+}
+
+/// Whether the program is installed, either as a executable path or in the `PATH`.
+pub fn installed(program: &str) -> bool {
+    if crate::config::FLATPAK {
+        return Command::new("flatpak-spawn")
+            .args(["--host", "sh", "-c", "command -v \"$1\"", "sh", program])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or_default();
+    }
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return is_executable(path);
+    }
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| is_executable(&dir.join(program))))
+        .unwrap_or_default()
+}
+
+/// Check that the program of the command can be run by running it with `--version`, returning
+/// the first line it printed.
+pub fn test(command: &str) -> Result<String, Error> {
+    let args = parse(command)?;
+    let program = &args[0];
+    let output = host_command(&[program.clone(), "--version".to_owned()])
+        .output()
+        .map_err(|e| Error::Run(program.clone(), e))?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or(program)
+        .to_owned())
+}
+
+/// Run the program with the arguments, the first argument being the program, and wait for it to
+/// exit.
+///
+/// `stdout` is called with every line the program prints. The end of its standard error is kept
+/// to report a failure.
+pub fn run<F: FnMut(&str)>(args: &[String], mut stdout: F) -> Result<(), Error> {
+    let program = &args[0];
+    let mut child = host_command(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::Run(program.clone(), e))?;
+
+    // Read the standard error at the same time, a full pipe would block the program.
+    let stderr = child.stderr.take().map(|stderr| {
+        thread::spawn(move || {
+            let mut lines = VecDeque::with_capacity(STDERR_LINES);
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                log::debug!("{}", line);
+                if lines.len() == STDERR_LINES {
+                    lines.pop_front();
+                }
+                lines.push_back(line);
+            }
+            Vec::from(lines).join("\n")
+        })
+    });
+    if let Some(out) = child.stdout.take() {
+        for line in BufReader::new(out).lines().map_while(Result::ok) {
+            log::debug!("{}", line);
+            stdout(&line);
+        }
+    }
+
+    let status = child.wait().map_err(|e| Error::Run(program.clone(), e))?;
+    let stderr = stderr
+        .and_then(|stderr| stderr.join().ok())
+        .unwrap_or_default();
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Exit(program.clone(), status.code(), stderr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> Placeholders {
+        Placeholders {
+            url: Some("https://example.com/watch?v=1".to_owned()),
+            title: Some("A title; with $pecial \"characters\"".to_owned()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_quoted_arguments() {
+        assert_eq!(
+            parse(r#"mpv --ytdl-format="bestvideo[height<=720]" {url}"#).unwrap(),
+            vec!["mpv", "--ytdl-format=bestvideo[height<=720]", "{url}"]
+        );
+        assert!(matches!(parse("mpv \"{url}"), Err(Error::Parse(_))));
+        assert!(matches!(parse("  "), Err(Error::Empty)));
+        assert!(matches!(
+            parse("mpv {uri}"),
+            Err(Error::UnknownPlaceholder(p)) if p == "uri"
+        ));
+    }
+
+    #[test]
+    fn build_url_mid_command() {
+        assert_eq!(
+            build(
+                r#"mpv --ytdl-format="bestvideo[height<=720]" {url} --title={title} --fs"#,
+                &values()
+            )
+            .unwrap(),
+            vec![
+                "mpv",
+                "--ytdl-format=bestvideo[height<=720]",
+                "https://example.com/watch?v=1",
+                r#"--title=A title; with $pecial "characters""#,
+                "--fs"
+            ]
+        );
+    }
+
+    #[test]
+    fn build_appends_target() {
+        assert_eq!(
+            build("mpv --fs", &values()).unwrap(),
+            vec!["mpv", "--fs", "https://example.com/watch?v=1"]
+        );
+        let downloaded = Placeholders {
+            local_path: Some("/tmp/video.mp4".to_owned()),
+            ..values()
+        };
+        assert_eq!(
+            build("mpv --fs", &downloaded).unwrap(),
+            vec!["mpv", "--fs", "/tmp/video.mp4"]
+        );
+    }
+
+    #[test]
+    fn missing_program() {
+        let not_found = Error::Run(
+            "mpv".to_owned(),
+            std::io::Error::from(std::io::ErrorKind::NotFound),
+        );
+        assert_eq!(not_found.missing_program_on(false), Some("mpv"));
+        assert_eq!(not_found.missing_program_on(true), Some("mpv"));
+
+        // flatpak-spawn reports a program missing on the host by the exit code of its shell.
+        let exit = Error::Exit("mpv".to_owned(), Some(127), String::new());
+        assert_eq!(exit.missing_program_on(false), None);
+        assert_eq!(exit.missing_program_on(true), Some("mpv"));
+
+        let failed = Error::Exit("mpv".to_owned(), Some(2), String::new());
+        assert_eq!(failed.missing_program_on(true), None);
+    }
+
+    #[test]
+    fn build_missing_local_path() {
+        // The video was not downloaded, play the url instead.
+        assert_eq!(
+            build("mpv {local_path} --author={author}", &values()).unwrap(),
+            vec!["mpv", "--author=", "https://example.com/watch?v=1"]
+        );
+    }
+}
@@ -0,0 +1,398 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Comments of videos.
+//!
+//! For YouTube, the comments are loaded from the instances of the backends (see
+//! [crate::youtube]), for PeerTube from the API of the instance. The comments are loaded in pages,
+//! where each page knows how to [Continue] with the next page or the replies of a comment.
+
+use serde::Deserialize;
+use tf_join::Platform;
+
+use crate::details::html_to_text;
+use crate::instances;
+use crate::youtube::{ordered_instances, video_id, Backend};
+
+/// The number of PeerTube comment threads loaded per page.
+const PEERTUBE_PAGE_SIZE: usize = 20;
+
+/// A comment of a video.
+#[derive(Debug, Clone)]
+pub struct Comment {
+    pub author: String,
+    /// The comment as plain text.
+    pub text: String,
+    /// When the comment was written, already formatted by the platform.
+    pub published: Option<String>,
+    pub likes: Option<i64>,
+    pub reply_count: i64,
+    /// How to load the replies to this comment, if there are any.
+    pub replies: Option<Continue>,
+}
+
+/// A page of comments.
+#[derive(Debug, Clone, Default)]
+pub struct CommentPage {
+    pub comments: Vec<Comment>,
+    /// How to load the next page, if there is any.
+    pub next: Option<Continue>,
+}
+
+/// How to load the next page of comments.
+///
+/// The tokens of the backends are only valid on the instance which created them.
+#[derive(Debug, Clone)]
+pub enum Continue {
+    Piped {
+        instance: String,
+        video_id: String,
+        nextpage: String,
+    },
+    Invidious {
+        instance: String,
+        video_id: String,
+        continuation: String,
+    },
+    PeertubeThreads {
+        api_url: String,
+        start: usize,
+    },
+    PeertubeReplies {
+        api_url: String,
+        thread: i64,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PipedComments {
+    #[serde(default)]
+    comments: Vec<PipedComment>,
+    nextpage: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PipedComment {
+    author: String,
+    comment_text: String,
+    commented_time: Option<String>,
+    #[serde(default)]
+    like_count: i64,
+    #[serde(default)]
+    reply_count: i64,
+    replies_page: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct InvidiousComments {
+    #[serde(default)]
+    comments: Vec<InvidiousComment>,
+    continuation: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvidiousComment {
+    author: String,
+    content: String,
+    published_text: Option<String>,
+    #[serde(default)]
+    like_count: i64,
+    replies: Option<InvidiousReplies>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InvidiousReplies {
+    reply_count: i64,
+    continuation: String,
+}
+
+#[derive(Deserialize)]
+struct PeertubeThreads {
+    data: Vec<PeertubeComment>,
+    total: usize,
+}
+
+#[derive(Deserialize)]
+struct PeertubeThread {
+    comment: PeertubeComment,
+    #[serde(default)]
+    children: Vec<PeertubeThread>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PeertubeComment {
+    id: i64,
+    #[serde(default)]
+    text: String,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    is_deleted: bool,
+    #[serde(default)]
+    total_replies: i64,
+    account: Option<PeertubeAccount>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PeertubeAccount {
+    display_name: String,
+}
+
+impl PeertubeComment {
+    fn into_comment(self, api_url: &str, with_replies: bool) -> Comment {
+        Comment {
+            author: self
+                .account
+                .map(|a| a.display_name)
+                .unwrap_or_else(|| gettextrs::gettext("Deleted")),
+            text: if self.is_deleted {
+                gettextrs::gettext("This comment has been deleted")
+            } else {
+                html_to_text(&self.text).trim().to_owned()
+            },
+            published: self.created_at.map(|d| {
+                d.with_timezone(&chrono::Local)
+                    .format(&gettextrs::gettext("%F %T"))
+                    .to_string()
+            }),
+            likes: None,
+            reply_count: self.total_replies,
+            replies: Some(Continue::PeertubeReplies {
+                api_url: api_url.to_owned(),
+                thread: self.id,
+            })
+            .filter(|_| with_replies && self.total_replies > 0),
+        }
+    }
+}
+
+/// Flatten the replies of a PeerTube thread, such that replies to replies are also shown.
+fn flatten_replies(children: Vec<PeertubeThread>, api_url: &str, comments: &mut Vec<Comment>) {
+    for child in children {
+        comments.push(child.comment.into_comment(api_url, false));
+        flatten_replies(child.children, api_url, comments);
+    }
+}
+
+async fn get<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    request: reqwest::RequestBuilder,
+) -> Result<T, String> {
+    let response = crate::http_cache::send(client, request)
+        .await
+        .map_err(|e| e.to_string())?;
+    serde_json::from_slice(&response).map_err(|e| e.to_string())
+}
+
+/// Whether comments of videos of the platform can be loaded.
+pub fn supported(platform: &Platform) -> bool {
+    matches!(platform, Platform::Youtube | Platform::Peertube)
+}
+
+/// Get the first page of comments of the video with the given url.
+pub async fn comments(url: String, platform: Platform) -> Option<CommentPage> {
+    match platform {
+        Platform::Youtube => youtube_comments(&video_id(&url)?).await,
+        Platform::Peertube => {
+            let api_url = crate::peertube::api_url(&url)?;
+            more(Continue::PeertubeThreads { api_url, start: 0 }).await
+        }
+        // LBRY comments are not supported.
+        Platform::Lbry => None,
+    }
+}
+
+async fn youtube_comments(id: &str) -> Option<CommentPage> {
+    let client = reqwest::Client::new();
+    for (backend, instance) in ordered_instances() {
+        let result = match backend {
+            Backend::Piped => {
+                piped_comments(
+                    &client,
+                    client.get(format!(
+                        "{}/comments/{}",
+                        instance.trim_end_matches('/'),
+                        id
+                    )),
+                    &instance,
+                    id,
+                )
+                .await
+            }
+            Backend::Invidious => invidious_comments(&client, &instance, id, None).await,
+        };
+        match result {
+            Ok(page) => {
+                instances::report_success(&instance);
+                return Some(page);
+            }
+            Err(e) => {
+                log::error!("Failed to get comments from {}: {}", instance, e);
+                instances::report_failure(&instance);
+            }
+        }
+    }
+    None
+}
+
+async fn piped_comments(
+    client: &reqwest::Client,
+    request: reqwest::RequestBuilder,
+    instance: &str,
+    id: &str,
+) -> Result<CommentPage, String> {
+    let comments: PipedComments = get(client, request).await?;
+    let next = |nextpage: String| Continue::Piped {
+        instance: instance.to_owned(),
+        video_id: id.to_owned(),
+        nextpage,
+    };
+    Ok(CommentPage {
+        comments: comments
+            .comments
+            .into_iter()
+            .map(|c| Comment {
+                author: c.author,
+                text: html_to_text(&c.comment_text),
+                published: c.commented_time,
+                likes: Some(c.like_count).filter(|l| l >= &0),
+                reply_count: c.reply_count,
+                replies: c.replies_page.map(next),
+            })
+            .collect(),
+        next: comments.nextpage.map(next),
+    })
+}
+
+async fn invidious_comments(
+    client: &reqwest::Client,
+    instance: &str,
+    id: &str,
+    continuation: Option<&str>,
+) -> Result<CommentPage, String> {
+    let mut request = client.get(format!(
+        "{}/api/v1/comments/{}",
+        instance.trim_end_matches('/'),
+        id
+    ));
+    if let Some(continuation) = continuation {
+        request = request.query(&[("continuation", continuation)]);
+    }
+    let comments: InvidiousComments = get(client, request).await?;
+    let next = |continuation: String| Continue::Invidious {
+        instance: instance.to_owned(),
+        video_id: id.to_owned(),
+        continuation,
+    };
+    Ok(CommentPage {
+        comments: comments
+            .comments
+            .into_iter()
+            .map(|c| Comment {
+                author: c.author,
+                text: c.content,
+                published: c.published_text,
+                likes: Some(c.like_count).filter(|l| l >= &0),
+                reply_count: c
+                    .replies
+                    .as_ref()
+                    .map(|r| r.reply_count)
+                    .unwrap_or_default(),
+                replies: c.replies.map(|r| next(r.continuation)),
+            })
+            .collect(),
+        next: comments.continuation.map(next),
+    })
+}
+
+/// Load the next page of comments, or the replies to a comment.
+pub async fn more(next: Continue) -> Option<CommentPage> {
+    let client = reqwest::Client::new();
+    let result = match &next {
+        Continue::Piped {
+            instance,
+            video_id,
+            nextpage,
+        } => {
+            piped_comments(
+                &client,
+                client
+                    .get(format!(
+                        "{}/nextpage/comments/{}",
+                        instance.trim_end_matches('/'),
+                        video_id
+                    ))
+                    .query(&[("nextpage", nextpage)]),
+                instance,
+                video_id,
+            )
+            .await
+        }
+        Continue::Invidious {
+            instance,
+            video_id,
+            continuation,
+        } => invidious_comments(&client, instance, video_id, Some(continuation)).await,
+        Continue::PeertubeThreads { api_url, start } => get::<PeertubeThreads>(
+            &client,
+            client.get(format!("{}/comment-threads", api_url)).query(&[
+                ("start", start.to_string()),
+                ("count", PEERTUBE_PAGE_SIZE.to_string()),
+                ("sort", "-createdAt".to_owned()),
+            ]),
+        )
+        .await
+        .map(|threads| {
+            let end = start + threads.data.len();
+            CommentPage {
+                comments: threads
+                    .data
+                    .into_iter()
+                    .map(|c| c.into_comment(api_url, true))
+                    .collect(),
+                next: Some(Continue::PeertubeThreads {
+                    api_url: api_url.clone(),
+                    start: end,
+                })
+                .filter(|_| end < threads.total && end > *start),
+            }
+        }),
+        Continue::PeertubeReplies { api_url, thread } => get::<PeertubeThread>(
+            &client,
+            client.get(format!("{}/comment-threads/{}", api_url, thread)),
+        )
+        .await
+        .map(|thread| {
+            let mut comments = vec![];
+            flatten_replies(thread.children, api_url, &mut comments);
+            CommentPage {
+                comments,
+                next: None,
+            }
+        }),
+    };
+    result
+        .map_err(|e| log::error!("Failed to get more comments: {}", e))
+        .ok()
+}
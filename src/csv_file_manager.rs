@@ -31,6 +31,8 @@ use tf_join::{AnySubscription, SubscriptionEvent};
 use tf_observer::Observer;
 use tf_playlist::PlaylistEvent;
 
+use crate::provider::{ProviderSubscription, ProviderSubscriptionEvent};
+
 pub(crate) struct CsvFileManager<T> {
     path: PathBuf,
     _phantom: PhantomData<T>,
@@ -116,6 +118,16 @@ impl TryFrom<SubscriptionEvent> for CsvEvent<AnySubscription> {
     }
 }
 
+impl TryFrom<ProviderSubscriptionEvent> for CsvEvent<ProviderSubscription> {
+    type Error = ();
+    fn try_from(e: ProviderSubscriptionEvent) -> Result<Self, ()> {
+        match e {
+            ProviderSubscriptionEvent::Add(i) => Ok(CsvEvent::Add(i)),
+            ProviderSubscriptionEvent::Remove(i) => Ok(CsvEvent::Remove(i)),
+        }
+    }
+}
+
 impl<T> TryFrom<FilterEvent<T>> for CsvEvent<T> {
     type Error = ();
     fn try_from(e: FilterEvent<T>) -> Result<Self, ()> {
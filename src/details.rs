@@ -0,0 +1,141 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Details of a single video, e.g. the description, which are not contained in the feed.
+//!
+//! These are loaded from the APIs of the platforms when the details of a video are shown.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tf_join::Platform;
+
+/// Matches timestamps like `1:23` or `1:02:03`.
+pub static TIMESTAMP: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(?:(\d{1,2}):)?(\d{1,2}):(\d{2})\b").expect("Regex to be valid"));
+
+/// A chapter of a video.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    /// The start of the chapter in seconds.
+    pub start: i64,
+    pub title: String,
+}
+
+/// The details of a video.
+#[derive(Debug, Clone, Default)]
+pub struct VideoDetails {
+    /// The description as plain text.
+    pub description: String,
+    pub tags: Vec<String>,
+    pub chapters: Vec<Chapter>,
+    pub uploaded: Option<chrono::NaiveDateTime>,
+    pub views: Option<i64>,
+    pub likes: Option<i64>,
+}
+
+impl VideoDetails {
+    /// Take the chapters from the description if the platform does not provide them.
+    pub fn with_description_chapters(mut self) -> Self {
+        if self.chapters.is_empty() {
+            self.chapters = chapters_from_description(&self.description);
+        }
+        self
+    }
+}
+
+/// Get the details of the video with the given url.
+pub async fn video_details(url: String, platform: Platform) -> Option<VideoDetails> {
+    let details = match platform {
+        Platform::Youtube => crate::youtube::video_info(crate::youtube::video_id(&url)?).await,
+        Platform::Peertube => crate::peertube::video_details(&url).await,
+        Platform::Lbry => crate::lbry::video_details(&url).await,
+    };
+    details.map(|d| d.with_description_chapters())
+}
+
+/// Parse a timestamp like `1:02:03` into seconds.
+pub fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    let captures = TIMESTAMP.captures(timestamp)?;
+    let part = |i| {
+        captures
+            .get(i)
+            .and_then(|m| m.as_str().parse::<i64>().ok())
+            .unwrap_or_default()
+    };
+    Some(part(1) * 3600 + part(2) * 60 + part(3))
+}
+
+/// Get the chapters from lines of the description starting with a timestamp.
+///
+/// Like YouTube, these are only considered chapters if there are at least two of them and the
+/// first starts at the beginning of the video.
+pub fn chapters_from_description(description: &str) -> Vec<Chapter> {
+    let chapters: Vec<Chapter> = description
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start_matches(|c: char| !c.is_alphanumeric());
+            let timestamp = TIMESTAMP.find(line).filter(|m| m.start() == 0)?;
+            let title = line[timestamp.end()..]
+                .trim_matches(|c: char| c.is_whitespace() || "-–—:|)]".contains(c))
+                .to_owned();
+            Some(Chapter {
+                start: parse_timestamp(timestamp.as_str())?,
+                title,
+            })
+        })
+        .collect();
+    if chapters.len() >= 2 && chapters[0].start == 0 {
+        chapters
+    } else {
+        vec![]
+    }
+}
+
+/// Convert a HTML description, as returned by e.g. Piped, into plain text.
+pub fn html_to_text(html: &str) -> String {
+    static LINE_BREAK: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?i)<br\s*/?>|</p>").expect("Regex to be valid"));
+    static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").expect("Regex to be valid"));
+    let text = LINE_BREAK.replace_all(html, "\n");
+    TAG.replace_all(&text, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// The url of the video with the given url, starting at the given time in seconds.
+pub fn url_at(url: &str, platform: Option<Platform>, seconds: i64) -> String {
+    let parameter = match platform {
+        Some(Platform::Peertube) => "start",
+        _ => "t",
+    };
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) => {
+            parsed
+                .query_pairs_mut()
+                .append_pair(parameter, &seconds.to_string());
+            parsed.to_string()
+        }
+        Err(_) => url.to_owned(),
+    }
+}
@@ -17,54 +17,52 @@
  * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::{fmt::Display, process::Command, thread};
+use std::thread;
+
+use crate::command::{self, Placeholders};
 
 const DOWNLOAD_MERGE: &str = "[Merger] Merging formats into ";
 const DOWNLOAD_DESTINATION: &str = "[download] Destination: ";
 
-pub fn download<
-    S: 'static + AsRef<str> + Display + std::convert::AsRef<std::ffi::OsStr> + std::marker::Send,
-    F: Fn(Option<String>) + std::marker::Send + 'static + std::marker::Sync,
->(
-    url: S,
-    callback: F,
-) {
-    log::debug!("Downloading video with url: {}", url);
-    let download_dir = std::env::var("XDG_DOWNLOAD_DIR")
-        .unwrap_or("$HOME/Downloads/%(title)s-%(id)s.%(ext)s".to_string());
-    let downloader_str =
-        std::env::var("DOWNLOADER").unwrap_or(format!("youtube-dl --output {}", download_dir));
-    open_with_output(url, downloader_str, move |output| {
-        callback(
-            output
-                .lines()
-                .into_iter()
-                .rev()
-                .find(|s| s.starts_with(DOWNLOAD_MERGE) || s.starts_with(DOWNLOAD_DESTINATION))
-                .map(|s| s.strip_prefix(DOWNLOAD_MERGE).unwrap_or(s))
-                .map(|s| s.strip_prefix(DOWNLOAD_DESTINATION).unwrap_or(s))
-                .map(|s| s.trim_matches('"').to_owned()),
-        )
-    });
+/// The template of the path to download videos to, as understood by youtube-dl and yt-dlp.
+pub fn output_template() -> String {
+    std::env::var("XDG_DOWNLOAD_DIR")
+        .unwrap_or("$HOME/Downloads/%(title)s-%(id)s.%(ext)s".to_string())
 }
 
-pub fn open_with_output<
-    S: 'static + AsRef<str> + Display + std::convert::AsRef<std::ffi::OsStr> + std::marker::Send,
-    F: Fn(String) + std::marker::Send + 'static,
->(
-    url: S,
-    command: String,
+/// Download the video described by the placeholders, calling `callback` with the path of the
+/// downloaded file if it is known, or with the error if the downloader could not be run or
+/// failed.
+pub fn download<F: FnOnce(Result<Option<String>, command::Error>) + std::marker::Send + 'static>(
+    values: Placeholders,
     callback: F,
 ) {
+    log::debug!("Downloading video with url: {:?}", values.url);
+    let downloader_str = crate::environment::var("DOWNLOADER").unwrap_or(format!(
+        "youtube-dl --output {}",
+        shell_words::quote(&output_template())
+    ));
+    let args = match command::build(&downloader_str, &values) {
+        Ok(args) => args,
+        Err(e) => {
+            log::error!("Invalid downloader command: {}", e);
+            callback(Err(e));
+            return;
+        }
+    };
     thread::spawn(move || {
-        let mut command_iter = command.split(" ");
-        let program = command_iter
-            .next()
-            .expect("The command should have a program");
-        let args: Vec<String> = command_iter.map(|s| s.to_string()).collect();
-
-        let out = Command::new(&program).args(args).arg(url).output();
-
-        callback(String::from_utf8_lossy(&out.map(|o| o.stdout).unwrap_or_default()).to_string());
+        let mut path = None;
+        let result = command::run(&args, |line| {
+            if let Some(p) = line
+                .strip_prefix(DOWNLOAD_MERGE)
+                .or_else(|| line.strip_prefix(DOWNLOAD_DESTINATION))
+            {
+                path = Some(p.trim_matches('"').to_owned());
+            }
+        });
+        if let Err(e) = &result {
+            log::error!("Failed to download the video: {}", e);
+        }
+        callback(result.map(|_| path));
     });
 }
@@ -0,0 +1,48 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The configuration of the application, given as environment variables.
+//!
+//! The variables are initialized from the settings on startup, unless they are already set in
+//! the environment of the process, and changed by the preferences later on. The changes are not
+//! written to the environment of the process, as other threads may read it at the same time,
+//! which is undefined behaviour. They are kept in a map instead, which is consulted first.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use once_cell::sync::Lazy;
+
+static VARIABLES: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(Default::default);
+
+/// The value of the variable, `None` if it is not set or not unicode.
+pub fn var(key: &str) -> Option<String> {
+    let variables = VARIABLES.lock().expect("Variables to be lockable");
+    variables
+        .get(key)
+        .cloned()
+        .or_else(|| std::env::var(key).ok())
+}
+
+/// Set the variable for the application.
+pub fn set_var<V: Into<String>>(key: &str, value: V) {
+    VARIABLES
+        .lock()
+        .expect("Variables to be lockable")
+        .insert(key.to_owned(), value.into());
+}
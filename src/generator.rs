@@ -0,0 +1,158 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Generation of the feed.
+//!
+//! This replaces [Joiner::generate][tf_core::Generator::generate], which always uses piped for
+//! YouTube. The videos are generated per subscription, limited by the [Scheduler], and upgraded
+//! using the [Joiner] afterwards, such that they are still shared with e.g. the watch later list.
+
+use std::{
+    cmp::Reverse,
+    sync::{Arc, Mutex},
+};
+
+use futures::{stream::FuturesUnordered, StreamExt};
+use tf_core::{ErrorStore, ExpandedVideo, Video};
+use tf_filter::Filter;
+use tf_join::{AnySubscription, AnyVideo, Joiner};
+
+use crate::scheduler::{Failure, Scheduler};
+use crate::youtube::Backend;
+
+/// The host the videos of the subscription are requested from, used for rate limiting.
+///
+/// For YouTube, this is the instance that will be tried first.
+fn host(subscription: &AnySubscription) -> Option<String> {
+    let url = match subscription {
+        AnySubscription::Youtube(_) => crate::instances::ordered(Backend::preferred())
+            .first()?
+            .clone(),
+        AnySubscription::Peertube(s) => s.base_url(),
+        AnySubscription::Lbry(_) => "https://odysee.com".to_owned(),
+    };
+    reqwest::Url::parse(&url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_owned()))
+}
+
+/// Generate the videos of all subscriptions of the [Joiner], newest first.
+pub async fn generate(
+    joiner: &Joiner,
+    errors: &ErrorStore,
+    scheduler: &Scheduler,
+) -> Vec<AnyVideo> {
+    generate_progressive(joiner, errors, scheduler, |_| {}).await
+}
+
+/// Generate the videos of all subscriptions of the [Joiner], newest first.
+///
+/// `on_generated` is called with the result of every subscription as soon as it is generated.
+pub async fn generate_progressive<F>(
+    joiner: &Joiner,
+    errors: &ErrorStore,
+    scheduler: &Scheduler,
+    on_generated: F,
+) -> Vec<AnyVideo>
+where
+    F: Fn(Result<Vec<AnyVideo>, Failure<AnySubscription>>),
+{
+    let client = reqwest::Client::new();
+    let subscriptions: Vec<AnySubscription> = joiner.subscription_list().iter().collect();
+    let mut results: FuturesUnordered<_> = subscriptions
+        .iter()
+        .map(|s| generate_scheduled(joiner, s, errors, scheduler, &client))
+        .collect();
+
+    let mut videos = vec![];
+    while let Some(result) = results.next().await {
+        if let Ok(generated) = &result {
+            videos.extend(generated.iter().cloned());
+        }
+        on_generated(result);
+    }
+    videos.sort_by_cached_key(|v| Reverse(v.uploaded()));
+    videos
+}
+
+/// Generate the (filtered) videos of a single subscription using the [Scheduler].
+pub async fn generate_scheduled(
+    joiner: &Joiner,
+    subscription: &AnySubscription,
+    errors: &ErrorStore,
+    scheduler: &Scheduler,
+    client: &reqwest::Client,
+) -> Result<Vec<AnyVideo>, Failure<AnySubscription>> {
+    let mut videos =
+        scheduler
+            .fetch(
+                subscription.clone(),
+                host(subscription),
+                errors,
+                |errors| async move {
+                    generate_subscription(joiner, subscription, &errors, client).await
+                },
+            )
+            .await?;
+    let subscription_list = joiner.subscription_list();
+    videos
+        .iter()
+        .map(|v| v.subscription())
+        .for_each(|s| subscription_list.update(s));
+    {
+        let filters = joiner.filters();
+        let filters = filters.lock().expect("Filter Group to be lockable");
+        videos.retain(|v| !filters.matches(v));
+    }
+    Ok(videos)
+}
+
+/// Generate the videos of a single subscription.
+pub async fn generate_subscription(
+    joiner: &Joiner,
+    subscription: &AnySubscription,
+    errors: &ErrorStore,
+    client: &reqwest::Client,
+) -> Vec<AnyVideo> {
+    match subscription {
+        AnySubscription::Youtube(s) => crate::youtube::channel_videos(s, errors, client)
+            .await
+            .into_iter()
+            .map(|v| upgrade(joiner, v))
+            .collect(),
+        AnySubscription::Peertube(s) => crate::peertube::channel_videos(s, errors, client)
+            .await
+            .into_iter()
+            .map(|v| upgrade(joiner, v))
+            .collect(),
+        AnySubscription::Lbry(s) => crate::rss::videos(s, errors, client)
+            .await
+            .into_iter()
+            .map(|v| upgrade(joiner, v))
+            .collect(),
+    }
+}
+
+fn upgrade<V>(joiner: &Joiner, video: V) -> AnyVideo
+where
+    V: Video,
+    Arc<Mutex<ExpandedVideo<V>>>: Into<AnyVideo>,
+{
+    joiner.upgrade_video(&Arc::new(Mutex::new(ExpandedVideo::from(video))).into())
+}
@@ -0,0 +1,72 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use gtk::gio::Notification;
+use gtk::prelude::*;
+use libadwaita::traits::MessageDialogExt;
+use libadwaita::MessageDialog;
+
+use crate::command::Error;
+use crate::gui::preferences_window::PreferencesWindow;
+
+/// Show the error of running the player or downloader in a dialog, offering to open the
+/// preferences to change the command.
+///
+/// If no window is shown, e.g. when running in the background, a notification is sent instead.
+pub fn show(heading: &str, error: &Error) {
+    let app = match gtk::gio::Application::default() {
+        Some(app) => app,
+        None => {
+            log::error!("{}: {}", heading, error);
+            return;
+        }
+    };
+    let parent = app
+        .downcast_ref::<gtk::Application>()
+        .and_then(|app| app.active_window())
+        .filter(|window| window.is_visible());
+    let parent = match parent {
+        Some(parent) => parent,
+        None => {
+            log::error!("{}: {}", heading, error);
+            let notification = Notification::new(heading);
+            notification.set_body(Some(&error.to_string()));
+            notification.set_default_action("app.show-feed");
+            app.send_notification(Some("command-error"), &notification);
+            return;
+        }
+    };
+
+    let dialog = MessageDialog::builder()
+        .heading(heading)
+        .body(error.to_string())
+        .transient_for(&parent)
+        .modal(true)
+        .build();
+    dialog.add_response("close", &gettextrs::gettext("Close"));
+    dialog.add_response("preferences", &gettextrs::gettext("Preferences"));
+    dialog.set_default_response(Some("close"));
+    dialog.set_close_response("close");
+    dialog.connect_response(Some("preferences"), move |_, _| {
+        let preferences = PreferencesWindow::new();
+        preferences.set_transient_for(Some(&parent));
+        preferences.show();
+    });
+    dialog.show();
+}
@@ -0,0 +1,254 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+use std::future::Future;
+
+use gdk::glib::{clone, MainContext, PRIORITY_DEFAULT};
+use gdk::subclass::prelude::ObjectSubclassIsExt;
+use gtk::glib::{self, Object};
+use gtk::prelude::*;
+
+use crate::comments::{Comment, CommentPage};
+
+use super::feed_item_object::VideoObject;
+
+gtk::glib::wrapper! {
+    pub struct CommentsWindow(ObjectSubclass<imp::CommentsWindow>)
+        @extends libadwaita::Window, gtk::Window, gtk::Widget,
+        @implements gtk::gio::ActionGroup, gtk::gio::ActionMap, gtk::Accessible, gtk::Buildable,
+            gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+impl CommentsWindow {
+    /// Create a window showing the comments of the given video.
+    pub fn new(video: &VideoObject) -> Self {
+        let s: Self = Object::builder().property("video", video).build();
+        s.load();
+        s
+    }
+
+    fn load(&self) {
+        let container = self.imp().box_comments.get();
+        let url = self.imp().video.borrow().as_ref().and_then(|v| {
+            v.property::<Option<String>>("url")
+                .zip(v.video().map(|v| v.platform()))
+        });
+        match url {
+            Some((url, platform)) if crate::comments::supported(&platform) => {
+                load_page(container, crate::comments::comments(url, platform))
+            }
+            _ => container.append(&status_label(&gettextrs::gettext(
+                "Comments are not available for this video",
+            ))),
+        }
+    }
+}
+
+fn status_label(label: &str) -> gtk::Label {
+    let label = gtk::Label::new(Some(label));
+    label.add_css_class("dim-label");
+    label.set_wrap(true);
+    label
+}
+
+/// Load a page of comments and append it to the container, followed by a button to load the
+/// next page.
+fn load_page<F>(container: gtk::Box, page: F)
+where
+    F: Future<Output = Option<CommentPage>> + Send + 'static,
+{
+    let spinner = gtk::Spinner::builder().spinning(true).build();
+    container.append(&spinner);
+
+    let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+    tokio::spawn(async move {
+        let _ = sender.send(page.await);
+    });
+    receiver.attach(
+        None,
+        clone!(@weak container => @default-return Continue(false), move |page: Option<CommentPage>| {
+            container.remove(&spinner);
+            let page = match page {
+                Some(page) => page,
+                None => {
+                    container.append(&status_label(&gettextrs::gettext("Failed to load comments")));
+                    return Continue(false);
+                }
+            };
+            if page.comments.is_empty() && container.first_child().is_none() {
+                container.append(&status_label(&gettextrs::gettext("No Comments")));
+            }
+            for comment in page.comments {
+                container.append(&comment_widget(comment));
+            }
+            if let Some(next) = page.next {
+                let button = gtk::Button::builder()
+                    .label(gettextrs::gettext("Load More"))
+                    .halign(gtk::Align::Center)
+                    .build();
+                button.connect_clicked(clone!(@weak container => move |button| {
+                    container.remove(button);
+                    load_page(container, crate::comments::more(next.clone()));
+                }));
+                container.append(&button);
+            }
+            Continue(false)
+        }),
+    );
+}
+
+fn comment_widget(comment: Comment) -> gtk::Box {
+    let widget = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(4)
+        .build();
+
+    let header = gtk::Box::builder().spacing(8).build();
+    let author = gtk::Label::builder()
+        .label(&comment.author)
+        .ellipsize(gtk::pango::EllipsizeMode::End)
+        .build();
+    author.add_css_class("heading");
+    header.append(&author);
+
+    let likes = comment.likes.map(|l| {
+        gettextrs::ngettext("{} like", "{} likes", l as u32).replace("{}", &l.to_string())
+    });
+    let info: Vec<String> = [comment.published, likes].into_iter().flatten().collect();
+    let label_info = gtk::Label::builder()
+        .label(info.join(" · "))
+        .ellipsize(gtk::pango::EllipsizeMode::End)
+        .build();
+    label_info.add_css_class("dim-label");
+    label_info.add_css_class("caption");
+    header.append(&label_info);
+    widget.append(&header);
+
+    let text = gtk::Label::builder()
+        .label(&comment.text)
+        .wrap(true)
+        .wrap_mode(gtk::pango::WrapMode::WordChar)
+        .selectable(true)
+        .xalign(0.0)
+        .build();
+    widget.append(&text);
+
+    if let Some(replies) = comment.replies {
+        let replies_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .margin_start(24)
+            .build();
+        let label = if comment.reply_count > 0 {
+            gettextrs::ngettext(
+                "Show {} Reply",
+                "Show {} Replies",
+                comment.reply_count as u32,
+            )
+            .replace("{}", &comment.reply_count.to_string())
+        } else {
+            gettextrs::gettext("Show Replies")
+        };
+        let button = gtk::Button::builder()
+            .label(label)
+            .halign(gtk::Align::Start)
+            .build();
+        button.add_css_class("flat");
+        button.connect_clicked(clone!(@weak replies_box => move |button| {
+            button.set_visible(false);
+            load_page(replies_box, crate::comments::more(replies.clone()));
+        }));
+        widget.append(&button);
+        widget.append(&replies_box);
+    }
+
+    widget
+}
+
+pub mod imp {
+    use std::cell::RefCell;
+
+    use gdk::glib::ParamSpecObject;
+    use gdk::glib::Value;
+    use glib::subclass::InitializingObject;
+    use glib::ParamSpec;
+    use gtk::glib;
+    use gtk::prelude::*;
+    use gtk::subclass::prelude::*;
+    use gtk::CompositeTemplate;
+    use libadwaita::subclass::prelude::AdwWindowImpl;
+    use once_cell::sync::Lazy;
+
+    use crate::gui::feed::feed_item_object::VideoObject;
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/ui/comments_window.ui")]
+    pub struct CommentsWindow {
+        #[template_child]
+        pub(super) box_comments: TemplateChild<gtk::Box>,
+
+        pub(super) video: RefCell<Option<VideoObject>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CommentsWindow {
+        const NAME: &'static str = "TFCommentsWindow";
+        type Type = super::CommentsWindow;
+        type ParentType = libadwaita::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for CommentsWindow {
+        fn properties() -> &'static [ParamSpec] {
+            static PROPERTIES: Lazy<Vec<ParamSpec>> =
+                Lazy::new(|| vec![ParamSpecObject::builder::<VideoObject>("video").build()]);
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &Value, pspec: &ParamSpec) {
+            match pspec.name() {
+                "video" => {
+                    let value: Option<VideoObject> =
+                        value.get().expect("Property video of incorrect type");
+                    self.video.replace(value);
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+            match pspec.name() {
+                "video" => self.video.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl WidgetImpl for CommentsWindow {}
+    impl WindowImpl for CommentsWindow {}
+    impl AdwWindowImpl for CommentsWindow {}
+}
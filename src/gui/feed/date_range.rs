@@ -0,0 +1,88 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Restricting the videos of a [FeedList][super::feed_list::FeedList] to a range of upload dates.
+
+use chrono::{NaiveDate, NaiveDateTime, TimeZone};
+use gdk::gio::Settings;
+use gdk::prelude::SettingsExt;
+
+/// The format of the dates of the custom range in the settings.
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The GSettings-keys the date range is read from, including the time of the last visit.
+pub const SETTINGS_KEYS: [&str; 5] = [
+    "date-range",
+    "date-range-days",
+    "date-range-from",
+    "date-range-to",
+    "last-visit",
+];
+
+/// The range of upload dates to show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateRange {
+    #[default]
+    All,
+    Today,
+    Yesterday,
+    /// The last days, including today.
+    LastDays(u32),
+    /// Everything uploaded after the given unix timestamp.
+    SinceLastVisit(i64),
+    /// Everything between the dates (inclusive), both are optional.
+    Custom(Option<NaiveDate>, Option<NaiveDate>),
+}
+
+impl DateRange {
+    pub fn from_settings(settings: &Settings) -> Self {
+        let date = |key: &str| NaiveDate::parse_from_str(&settings.string(key), DATE_FORMAT).ok();
+        match settings.string("date-range").as_str() {
+            "today" => DateRange::Today,
+            "yesterday" => DateRange::Yesterday,
+            "last-days" => DateRange::LastDays(settings.uint("date-range-days")),
+            "since-last-visit" => DateRange::SinceLastVisit(settings.int64("last-visit")),
+            "custom" => DateRange::Custom(date("date-range-from"), date("date-range-to")),
+            _ => DateRange::All,
+        }
+    }
+
+    /// Whether a video uploaded at the given time (in UTC) is inside of the range.
+    pub fn contains(&self, uploaded: Option<NaiveDateTime>) -> bool {
+        if self == &DateRange::All {
+            return true;
+        }
+        let uploaded = match uploaded {
+            Some(uploaded) => uploaded,
+            None => return false,
+        };
+        let date = chrono::Local.from_utc_datetime(&uploaded).date_naive();
+        let today = chrono::Local::now().date_naive();
+        match self {
+            DateRange::All => true,
+            DateRange::Today => date == today,
+            DateRange::Yesterday => date == today - chrono::Duration::days(1),
+            DateRange::LastDays(days) => date > today - chrono::Duration::days((*days).into()),
+            DateRange::SinceLastVisit(last_visit) => uploaded.timestamp() > *last_visit,
+            DateRange::Custom(from, to) => {
+                from.map(|f| f <= date).unwrap_or(true) && to.map(|t| date <= t).unwrap_or(true)
+            }
+        }
+    }
+}
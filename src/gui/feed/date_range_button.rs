@@ -0,0 +1,140 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+gtk::glib::wrapper! {
+    pub struct DateRangeButton(ObjectSubclass<imp::DateRangeButton>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+pub mod imp {
+    use gdk::gio::Settings;
+    use gdk::gio::SettingsBindFlags;
+    use gdk::gio::SimpleActionGroup;
+    use gdk::glib::clone;
+    use glib::subclass::InitializingObject;
+    use gtk::glib;
+    use gtk::prelude::*;
+    use gtk::subclass::prelude::*;
+    use gtk::CompositeTemplate;
+
+    use crate::gui::feed::date_range::DATE_FORMAT;
+
+    #[derive(CompositeTemplate)]
+    #[template(resource = "/ui/date_range_button.ui")]
+    pub struct DateRangeButton {
+        #[template_child]
+        btn_menu: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        spin_days: TemplateChild<gtk::SpinButton>,
+        #[template_child]
+        entry_from: TemplateChild<gtk::Entry>,
+        #[template_child]
+        entry_to: TemplateChild<gtk::Entry>,
+
+        settings: Settings,
+    }
+
+    impl DateRangeButton {
+        fn update(&self) {
+            let range = self.settings.string("date-range");
+            self.spin_days.set_sensitive(range == "last-days");
+            self.entry_from.set_sensitive(range == "custom");
+            self.entry_to.set_sensitive(range == "custom");
+            if range == "all" {
+                self.btn_menu.remove_css_class("accent");
+            } else {
+                self.btn_menu.add_css_class("accent");
+            }
+        }
+
+        fn bind_date(&self, key: &'static str, entry: &gtk::Entry) {
+            entry.set_text(&self.settings.string(key));
+            entry.set_placeholder_text(Some(&chrono::Local::now().format(DATE_FORMAT).to_string()));
+            entry.connect_changed(clone!(@strong self.settings as settings => move |entry| {
+                let text = entry.text();
+                let valid = text.is_empty()
+                    || chrono::NaiveDate::parse_from_str(&text, DATE_FORMAT).is_ok();
+                if valid {
+                    entry.remove_css_class("error");
+                    let _ = settings.set_string(key, &text);
+                } else {
+                    entry.add_css_class("error");
+                }
+            }));
+        }
+
+        fn setup(&self) {
+            let actions = SimpleActionGroup::new();
+            actions.add_action(&self.settings.create_action("date-range"));
+            self.obj().insert_action_group("date", Some(&actions));
+
+            self.settings
+                .bind("date-range-days", &self.spin_days.get(), "value")
+                .flags(SettingsBindFlags::DEFAULT)
+                .build();
+            self.bind_date("date-range-from", &self.entry_from);
+            self.bind_date("date-range-to", &self.entry_to);
+
+            self.update();
+            let obj = self.obj();
+            self.settings.connect_changed(
+                Some("date-range"),
+                clone!(@weak obj => move |_, _| {
+                    obj.imp().update();
+                }),
+            );
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DateRangeButton {
+        const NAME: &'static str = "TFDateRangeButton";
+        type Type = super::DateRangeButton;
+        type ParentType = gtk::Box;
+
+        fn new() -> Self {
+            Self {
+                btn_menu: TemplateChild::default(),
+                spin_days: TemplateChild::default(),
+                entry_from: TemplateChild::default(),
+                entry_to: TemplateChild::default(),
+                settings: Settings::new(crate::config::APP_ID),
+            }
+        }
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for DateRangeButton {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.setup();
+        }
+    }
+
+    impl WidgetImpl for DateRangeButton {}
+    impl BoxImpl for DateRangeButton {}
+}
@@ -20,6 +20,9 @@
 
 use gdk::subclass::prelude::ObjectSubclassIsExt;
 use tf_core::ErrorStore;
+use tf_join::AnySubscription;
+
+use crate::gui::subscription::subscription_item_object::SubscriptionObject;
 
 gtk::glib::wrapper! {
     pub struct ErrorLabel(ObjectSubclass<imp::ErrorLabel>)
@@ -33,9 +36,37 @@ impl ErrorLabel {
         self.imp().error_store.replace(Some(error_store));
         self.imp().setup(&self);
     }
+
+    /// List the subscription as failed with the given errors.
+    pub fn add_failure(&self, subscription: SubscriptionObject, errors: Vec<tf_core::Error>) {
+        self.imp().add_failure(subscription, errors);
+    }
+
+    /// Remove the subscription from the list of failed subscriptions, e.g. because it is retried.
+    pub fn remove_failure(&self, subscription: &SubscriptionObject) {
+        self.imp().remove_failure(subscription);
+    }
+}
+
+/// The url of the channel of the subscription on the web.
+fn subscription_url(subscription: &AnySubscription) -> String {
+    match subscription {
+        AnySubscription::Youtube(s) => format!("https://www.youtube.com/channel/{}", s.id()),
+        AnySubscription::Peertube(s) => format!("{}/c/{}", s.base_url(), s.id()),
+        AnySubscription::Lbry(s) => format!("https://odysee.com/{}", s.id()),
+    }
+}
+
+fn error_description(error: &tf_core::Error) -> String {
+    let kind = match error {
+        tf_core::Error::NetworkError(_) => gettextrs::gettext("Network Error"),
+        tf_core::Error::ParseError(_) => gettextrs::gettext("Parse Error"),
+    };
+    format!("{}: {}", kind, error)
 }
 
 pub mod imp {
+    use std::cell::Cell;
     use std::cell::RefCell;
     use std::sync::Arc;
     use std::sync::Mutex;
@@ -43,35 +74,199 @@ pub mod imp {
     use gdk::glib::clone;
     use gdk::glib::MainContext;
     use gdk::glib::ParamSpec;
+    use gdk::glib::ParamSpecBoolean;
     use gdk::glib::ParamSpecString;
     use gdk::glib::Sender;
     use gdk::glib::Value;
     use gdk::glib::PRIORITY_DEFAULT;
     use glib::subclass::InitializingObject;
     use gtk::glib;
+    use gtk::glib::subclass::Signal;
     use gtk::prelude::*;
     use gtk::subclass::prelude::*;
 
     use gtk::CompositeTemplate;
+    use libadwaita::prelude::*;
+    use libadwaita::ActionRow;
     use once_cell::sync::Lazy;
     use tf_core::ErrorEvent;
     use tf_core::ErrorStore;
     use tf_observer::Observable;
     use tf_observer::Observer;
 
+    use crate::gui::subscription::subscription_item_object::SubscriptionObject;
     use crate::gui::utility::Utility;
 
+    /// A failed subscription listed in the [ErrorLabel].
+    struct Failure {
+        subscription: SubscriptionObject,
+        error_count: usize,
+        row: ActionRow,
+    }
+
     #[derive(CompositeTemplate, Default)]
     #[template(resource = "/ui/error_label.ui")]
     pub struct ErrorLabel {
+        #[template_child]
+        list_failures: TemplateChild<gtk::ListBox>,
+
         pub(super) error_store: RefCell<Option<ErrorStore>>,
 
         error: RefCell<Option<String>>,
+        failures: RefCell<Vec<Failure>>,
+        /// The number of errors in the store belonging to failures no longer listed, e.g. because
+        /// they were retried.
+        resolved_errors: Cell<usize>,
+        has_failures: Cell<bool>,
 
         _error_store_observer: RefCell<Option<Arc<Mutex<Box<dyn Observer<ErrorEvent> + Send>>>>>,
     }
 
     impl ErrorLabel {
+        fn suffix_button(icon: &str, tooltip: &str) -> gtk::Button {
+            let button = gtk::Button::from_icon_name(icon);
+            button.set_valign(gtk::Align::Center);
+            button.set_tooltip_text(Some(tooltip));
+            button.add_css_class("flat");
+            button
+        }
+
+        pub(super) fn add_failure(
+            &self,
+            subscription: SubscriptionObject,
+            errors: Vec<tf_core::Error>,
+        ) {
+            let obj = self.obj();
+            self.remove_failure(&subscription);
+
+            let row = ActionRow::new();
+            row.set_use_markup(false);
+            row.set_title(
+                &subscription
+                    .property::<Option<String>>("name")
+                    .unwrap_or_default(),
+            );
+            row.set_subtitle(
+                &errors
+                    .iter()
+                    .map(super::error_description)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+            row.set_subtitle_lines(3);
+
+            let btn_retry =
+                Self::suffix_button("view-refresh-symbolic", &gettextrs::gettext("Retry"));
+            btn_retry.connect_clicked(clone!(@weak obj, @strong subscription => move |_| {
+                obj.emit_by_name::<()>("retry", &[&subscription]);
+            }));
+            row.add_suffix(&btn_retry);
+
+            if let Some(url) = subscription
+                .subscription()
+                .map(|s| super::subscription_url(&s))
+            {
+                let btn_open = Self::suffix_button(
+                    "web-browser-symbolic",
+                    &gettextrs::gettext("Open in Browser"),
+                );
+                btn_open.connect_clicked(clone!(@weak obj => move |_| {
+                    let window = obj.root().and_then(|r| r.downcast::<gtk::Window>().ok());
+                    gtk::show_uri(window.as_ref(), &url, 0);
+                }));
+                row.add_suffix(&btn_open);
+            }
+
+            let btn_remove = Self::suffix_button(
+                "user-trash-symbolic",
+                &gettextrs::gettext("Remove Subscription"),
+            );
+            btn_remove.connect_clicked(clone!(@weak obj, @strong subscription => move |_| {
+                obj.remove_failure(&subscription);
+                obj.emit_by_name::<()>("remove", &[&subscription]);
+            }));
+            row.add_suffix(&btn_remove);
+
+            self.list_failures.append(&row);
+            self.failures.borrow_mut().push(Failure {
+                subscription,
+                error_count: errors.len(),
+                row,
+            });
+            self.update_error();
+        }
+
+        pub(super) fn remove_failure(&self, subscription: &SubscriptionObject) {
+            let is_subscription = |f: &Failure| {
+                f.subscription.subscription() == subscription.subscription()
+                    && f.subscription.provider_subscription()
+                        == subscription.provider_subscription()
+            };
+            let removed: Vec<Failure> = {
+                let mut failures = self.failures.borrow_mut();
+                let (removed, kept) = failures.drain(..).partition(is_subscription);
+                *failures = kept;
+                removed
+            };
+            for failure in &removed {
+                self.list_failures.remove(&failure.row);
+                self.resolved_errors
+                    .set(self.resolved_errors.get() + failure.error_count);
+            }
+            if !removed.is_empty() {
+                self.update_error();
+            }
+        }
+
+        fn clear_failures(&self) {
+            for failure in self.failures.take() {
+                self.list_failures.remove(&failure.row);
+            }
+            self.resolved_errors.set(0);
+        }
+
+        /// Summarize the errors of the store, which are not already listed as failed
+        /// subscriptions or belong to failures no longer listed.
+        fn update_error(&self) {
+            let obj = self.obj();
+            let error_store = self.error_store.borrow().clone().unwrap_or_default();
+            let failures = self.failures.borrow();
+            let failure_errors: usize = failures.iter().map(|f| f.error_count).sum();
+            let known_errors = failure_errors + self.resolved_errors.get();
+
+            let message = if error_store.iter().count() > known_errors {
+                let summary = error_store.summary();
+                if summary.network() > 0 {
+                    gettextrs::gettext("Error connecting to the network")
+                } else if summary.parse() > 0 {
+                    let msg = gettextrs::ngettext(
+                        "Error parsing one subscription",
+                        "Error parsing {} subscriptions",
+                        summary.parse() as u32,
+                    );
+                    msg.replace("{}", &summary.parse().to_string())
+                } else {
+                    gettextrs::gettext("Some error occured")
+                }
+            } else if !failures.is_empty() {
+                gettextrs::ngettext(
+                    "Failed to load one subscription",
+                    "Failed to load {} subscriptions",
+                    failures.len() as u32,
+                )
+                .replace("{}", &failures.len().to_string())
+            } else {
+                String::new()
+            };
+
+            obj.set_property("error", &message);
+            let has_failures = !failures.is_empty();
+            if self.has_failures.get() != has_failures {
+                self.has_failures.set(has_failures);
+                obj.notify("has-failures");
+            }
+        }
+
         pub(super) fn setup(&self, obj: &super::ErrorLabel) {
             let mut error_store = self
                 .error_store
@@ -94,22 +289,11 @@ pub mod imp {
                 clone!(@strong obj => move |error_event| {
                     match error_event {
                         ErrorEvent::Add(_e) => {
-                            let summary = error_store.summary();
-
-                            let message = if summary.network() > 0 {
-                                gettextrs::gettext("Error connecting to the network").to_string()
-                            } else if summary.parse() > 0 {
-                                let msg = gettextrs::ngettext("Error parsing one subscription", "Error parsing {} subscriptions", summary.parse() as u32);
-                                msg.replace("{}", &summary.parse().to_string()).to_string()
-                            } else {
-                                gettextrs::gettext("Some error occured").to_string()
-                            };
-
-                            obj.set_property("error", &message);
-
+                            obj.imp().update_error();
                         }
                         ErrorEvent::Clear => {
-                            obj.set_property("error", "");
+                            obj.imp().clear_failures();
+                            obj.imp().update_error();
                         }
                     }
                     Continue(true)
@@ -140,8 +324,14 @@ pub mod imp {
         }
 
         fn properties() -> &'static [ParamSpec] {
-            static PROPERTIES: Lazy<Vec<ParamSpec>> =
-                Lazy::new(|| vec![ParamSpecString::builder("error").build()]);
+            static PROPERTIES: Lazy<Vec<ParamSpec>> = Lazy::new(|| {
+                vec![
+                    ParamSpecString::builder("error").build(),
+                    ParamSpecBoolean::builder("has-failures")
+                        .read_only()
+                        .build(),
+                ]
+            });
             PROPERTIES.as_ref()
         }
 
@@ -159,9 +349,24 @@ pub mod imp {
         fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
             match pspec.name() {
                 "error" => self.error.borrow().to_value(),
+                "has-failures" => self.has_failures.get().to_value(),
                 _ => unimplemented!(),
             }
         }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    Signal::builder("retry")
+                        .param_types([SubscriptionObject::static_type()])
+                        .build(),
+                    Signal::builder("remove")
+                        .param_types([SubscriptionObject::static_type()])
+                        .build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
     }
 
     impl WidgetImpl for ErrorLabel {}
@@ -20,6 +20,7 @@
 
 use gdk::subclass::prelude::ObjectSubclassIsExt;
 use gtk::glib::Object;
+use gtk::traits::{OrientableExt, WidgetExt};
 use tf_join::AnyVideo;
 use tf_playlist::PlaylistManager;
 
@@ -36,11 +37,25 @@ impl FeedItem {
         s.imp().playlist_manager.replace(Some(playlist_manager));
         s
     }
+
+    /// Show the thumbnail above the information instead of next to it, as used in a grid.
+    pub fn set_grid(&self, grid: bool) {
+        let imp = self.imp();
+        let (orientation, align) = if grid {
+            (gtk::Orientation::Vertical, gtk::Align::Fill)
+        } else {
+            (gtk::Orientation::Horizontal, gtk::Align::Start)
+        };
+        imp.box_content.set_orientation(orientation);
+        imp.thumbnail.set_halign(align);
+        imp.thumbnail.set_hexpand(grid);
+    }
 }
 
 pub mod imp {
     use std::cell::RefCell;
 
+    use gdk::gio;
     use gdk::gio::SimpleAction;
     use gdk::gio::SimpleActionGroup;
     use gdk::glib::clone;
@@ -60,6 +75,7 @@ pub mod imp {
     use crate::gui::feed::feed_item_object::VideoObject;
     use crate::gui::feed::thumbnail::Thumbnail;
     use crate::gui::utility::Utility;
+    use crate::gui::window::Window;
 
     #[derive(CompositeTemplate, Default)]
     #[template(resource = "/ui/feed_item.ui")]
@@ -74,10 +90,14 @@ pub mod imp {
         label_date: TemplateChild<gtk::Label>,
 
         #[template_child]
-        thumbnail: TemplateChild<Thumbnail>,
+        pub(super) box_content: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub(super) thumbnail: TemplateChild<Thumbnail>,
 
         #[template_child]
         watch_later: TemplateChild<gtk::Button>,
+        #[template_child]
+        section_open_with: TemplateChild<gio::Menu>,
 
         video: RefCell<Option<VideoObject>>,
         pub(super) playlist_manager: RefCell<Option<PlaylistManager<String, AnyVideo>>>,
@@ -85,6 +105,26 @@ pub mod imp {
 
     impl FeedItem {
         fn setup_actions(&self, obj: &super::FeedItem) {
+            let action_play = SimpleAction::new("play", None);
+            action_play.connect_activate(
+                clone!(@strong self.video as video, @weak obj => move |_, _| {
+                    let video = video.borrow();
+                    let video = video.as_ref().expect("Video should be set up");
+                    match obj.root().and_then(|r| r.downcast::<Window>().ok()) {
+                        Some(window) => window.play_video(video),
+                        None => video.play(),
+                    }
+                }),
+            );
+            let action_play_builtin = SimpleAction::new("play-builtin", None);
+            action_play_builtin.connect_activate(
+                clone!(@strong self.video as video, @weak obj => move |_, _| {
+                    let window = obj.root().and_then(|r| r.downcast::<Window>().ok());
+                    if let (Some(video), Some(window)) = (video.borrow().as_ref(), window) {
+                        window.play_builtin(video);
+                    }
+                }),
+            );
             let action_download = SimpleAction::new("download", None);
             action_download.connect_activate(clone!(@strong self.video as video => move |_, _| {
                 video.borrow().as_ref().expect("Video should be set up").download();
@@ -93,23 +133,155 @@ pub mod imp {
             action_clipboard.connect_activate(clone!(@strong self.video as video, @strong obj => move |_, _| {
                 let clipboard = obj.display().clipboard();
                 // Replace // with / because of simple bug I am too lazy to fix in the youtube-extractor.
-                clipboard.set_text(&video.borrow().as_ref().expect("Video should be set up").video().expect("Video should be set up").url().replace("//watch", "/watch"));
+                let url = video.borrow().as_ref().expect("Video should be set up").property::<Option<String>>("url").unwrap_or_default();
+                clipboard.set_text(&url.replace("//watch", "/watch"));
             }));
 
+            let action_details = SimpleAction::new("details", None);
+            action_details.connect_activate(
+                clone!(@strong self.video as video, @weak obj => move |_, _| {
+                    let window = obj.root().and_then(|r| r.downcast::<Window>().ok());
+                    if let (Some(video), Some(window)) = (video.borrow().as_ref(), window) {
+                        window.show_video(video);
+                    }
+                }),
+            );
+
+            let action_comments = SimpleAction::new("comments", None);
+            action_comments.set_enabled(
+                self.video
+                    .borrow()
+                    .as_ref()
+                    .and_then(|v| v.video())
+                    .map(|v| crate::comments::supported(&v.platform()))
+                    .unwrap_or_default(),
+            );
+            action_comments.connect_activate(
+                clone!(@strong self.video as video, @weak obj => move |_, _| {
+                    let window = obj.root().and_then(|r| r.downcast::<Window>().ok());
+                    if let (Some(video), Some(window)) = (video.borrow().as_ref(), window) {
+                        window.show_comments(video);
+                    }
+                }),
+            );
+
+            // Videos of providers cannot be put into the watch later list.
+            let watch_later_supported = self
+                .video
+                .borrow()
+                .as_ref()
+                .and_then(|v| v.video())
+                .is_some();
+            let action_watch_later = SimpleAction::new("watch-later", None);
+            action_watch_later.set_enabled(watch_later_supported);
+            let watch_later = self.watch_later.get();
+            watch_later.set_visible(watch_later_supported);
+            action_watch_later.connect_activate(clone!(@weak watch_later => move |_, _| {
+                watch_later.emit_clicked();
+            }));
+
+            let action_queue = SimpleAction::new("queue", None);
+            action_queue.connect_activate(
+                clone!(@strong self.video as video, @weak obj => move |_, _| {
+                    let window = obj.root().and_then(|r| r.downcast::<Window>().ok());
+                    if let (Some(video), Some(window)) = (video.borrow().as_ref(), window) {
+                        window.queue(std::slice::from_ref(video));
+                    }
+                }),
+            );
+
+            let action_queue_channel = SimpleAction::new("queue-channel", None);
+            action_queue_channel.connect_activate(
+                clone!(@strong self.video as video, @weak obj => move |_, _| {
+                    let window = obj.root().and_then(|r| r.downcast::<Window>().ok());
+                    if let (Some(video), Some(window)) = (video.borrow().as_ref(), window) {
+                        window.queue_channel(video);
+                    }
+                }),
+            );
+
+            let action_open_with = SimpleAction::new("open-with", Some(glib::VariantTy::STRING));
+            action_open_with.connect_activate(
+                clone!(@strong self.video as video => move |_, profile| {
+                    let profile = profile.and_then(|p| p.get::<String>()).unwrap_or_default();
+                    video.borrow().as_ref().expect("Video should be set up").open_with(&profile);
+                }),
+            );
+            self.update_open_with();
+
             let actions = SimpleActionGroup::new();
             obj.insert_action_group("item", Some(&actions));
+            actions.add_action(&action_play);
+            actions.add_action(&action_play_builtin);
+            actions.add_action(&action_details);
             actions.add_action(&action_download);
             actions.add_action(&action_clipboard);
+            actions.add_action(&action_comments);
+            actions.add_action(&action_watch_later);
+            actions.add_action(&action_queue);
+            actions.add_action(&action_queue_channel);
+            actions.add_action(&action_open_with);
+        }
+
+        /// Offer the players besides the one playing the video by default.
+        fn update_open_with(&self) {
+            self.section_open_with.remove_all();
+            let platform = self
+                .video
+                .borrow()
+                .as_ref()
+                .and_then(|v| v.property::<Option<String>>("platform"));
+            let submenu = gio::Menu::new();
+            // Offer the player the video is not played with by default.
+            if gio::Settings::new(crate::config::APP_ID).boolean("builtin-player") {
+                let player = crate::profiles::default(platform.as_deref());
+                let item = gio::MenuItem::new(Some(&player.label()), None);
+                item.set_action_and_target_value(
+                    Some("item.open-with"),
+                    Some(&player.name.to_variant()),
+                );
+                submenu.append_item(&item);
+            } else {
+                submenu.append(
+                    Some(&gettextrs::gettext("Built-in Player")),
+                    Some("item.play-builtin"),
+                );
+            }
+            for profile in crate::profiles::alternatives(platform.as_deref()) {
+                let item = gio::MenuItem::new(Some(&profile.label()), None);
+                item.set_action_and_target_value(
+                    Some("item.open-with"),
+                    Some(&profile.name.to_variant()),
+                );
+                submenu.append_item(&item);
+            }
+            self.section_open_with
+                .append_submenu(Some(&gettextrs::gettext("Open With…")), &submenu);
         }
         fn bind_watch_later(&self) {
             let video = &self.video;
             let playlist_manager = &self.playlist_manager;
+            let obj = self.obj();
             self.watch_later.connect_clicked(
-                clone!(@strong video, @strong playlist_manager => move |_| {
+                clone!(@strong video, @strong playlist_manager, @weak obj => move |_| {
                     let video = video.borrow().as_ref().map(|v| v.video()).flatten();
                     if let Some(video) = video {
-                        let mut playlist_manager = playlist_manager.borrow_mut();
-                        playlist_manager.as_mut().unwrap().toggle(&"WATCHLATER".to_owned(), &video);
+                        let mut playlist_manager = playlist_manager.borrow().clone().unwrap();
+                        let removed = playlist_manager.items(&"WATCHLATER".to_owned()).contains(&video);
+                        playlist_manager.toggle(&"WATCHLATER".to_owned(), &video);
+
+                        if !removed {
+                            return;
+                        }
+                        if let Some(window) = obj.root().and_then(|r| r.downcast::<Window>().ok()) {
+                            let title = gettextrs::gettext("Removed {} from Watch Later").replace("{}", &video.title());
+                            window.show_undo_toast(&title, move || {
+                                let mut playlist_manager = playlist_manager.clone();
+                                if !playlist_manager.items(&"WATCHLATER".to_owned()).contains(&video) {
+                                    playlist_manager.toggle(&"WATCHLATER".to_owned(), &video);
+                                }
+                            });
+                        }
                     }
                 }),
             );
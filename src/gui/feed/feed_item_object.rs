@@ -18,7 +18,7 @@
  *
  */
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 use gdk::glib;
 use gdk::subclass::prelude::ObjectSubclassIsExt;
@@ -29,8 +29,34 @@ use gdk::{
 use tf_core::Video;
 use tf_join::AnyVideo;
 
+use crate::command::{self, Placeholders};
 use crate::downloader::download;
-use crate::player::play;
+use crate::gui::command_error;
+use crate::metadata::{VideoKind, VideoMetadata};
+use crate::mpv;
+use crate::player::{play, play_playlist};
+use crate::profiles;
+use crate::provider::ProviderVideo;
+use crate::search::Query;
+use crate::watched::{resume_position, set_resume_position, ResumePosition};
+
+/// A message from a running player.
+enum PlayerMessage {
+    /// The playback changed.
+    Event(mpv::Event),
+    /// The player exited.
+    Exited(Result<(), command::Error>),
+}
+
+/// Playing less than this many seconds of a video does not remember the position.
+const RESUME_MIN_POSITION: f64 = 10.0;
+
+/// The played fraction of the video with the given url, as remembered from the last time.
+fn stored_progress(url: &str) -> f64 {
+    resume_position(url)
+        .map(|r| r.progress())
+        .unwrap_or_default()
+}
 
 macro_rules! str_prop {
     ( $x:expr ) => {
@@ -69,6 +95,41 @@ macro_rules! prop_get_all {
     }
 }
 
+/// Format a duration in seconds like `1:02:03` or `2:03`.
+pub(crate) fn format_duration(duration: i64) -> String {
+    let (hours, minutes, seconds) = (duration / 3600, duration / 60 % 60, duration % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Format a view count in a short form like `1.2K views`.
+pub(crate) fn format_views(views: i64) -> String {
+    let short = match views {
+        v if v >= 1_000_000_000 => format!("{:.1}B", v as f64 / 1_000_000_000.0),
+        v if v >= 1_000_000 => format!("{:.1}M", v as f64 / 1_000_000.0),
+        v if v >= 1_000 => format!("{:.1}K", v as f64 / 1_000.0),
+        v => v.to_string(),
+    };
+    gettextrs::ngettext(
+        "{} view",
+        "{} views",
+        views.clamp(0, u32::MAX.into()) as u32,
+    )
+    .replace("{}", &short)
+}
+
+fn format_kind(kind: VideoKind) -> Option<String> {
+    match kind {
+        VideoKind::Video => None,
+        VideoKind::Live => Some(gettextrs::gettext("Live")),
+        VideoKind::Upcoming => Some(gettextrs::gettext("Upcoming")),
+        VideoKind::Short => Some(gettextrs::gettext("Short")),
+    }
+}
+
 gtk::glib::wrapper! {
     pub struct VideoObject(ObjectSubclass<imp::VideoObject>);
 }
@@ -89,8 +150,36 @@ impl VideoObject {
                     .to_string(),
             )
             .property("playing", &false)
+            .property("watched", crate::watched::is_watched(&video.url()))
+            .property("progress", stored_progress(&video.url()))
             .build();
+        s.imp().metadata.replace(crate::metadata::get(video.url()));
         s.imp().video.swap(&RefCell::new(Some(video)));
+        s.set_metadata_properties();
+        s
+    }
+
+    pub fn from_provider(video: ProviderVideo) -> Self {
+        let s: Self = Object::builder::<Self>()
+            .property("title", &video.title)
+            .property("url", &video.url)
+            .property("thumbnail-url", &video.thumbnail_url)
+            .property("author", &video.author)
+            .property("platform", video.subscription.provider())
+            .property(
+                "date",
+                video
+                    .uploaded
+                    .format(&gettextrs::gettext("%F %T"))
+                    .to_string(),
+            )
+            .property("playing", false)
+            .property("watched", crate::watched::is_watched(&video.url))
+            .property("progress", stored_progress(&video.url))
+            .build();
+        s.imp().metadata.replace(Some(video.metadata.clone()));
+        s.imp().provider_video.swap(&RefCell::new(Some(video)));
+        s.set_metadata_properties();
         s
     }
 
@@ -98,45 +187,300 @@ impl VideoObject {
         self.imp().video.borrow().clone()
     }
 
+    pub fn provider_video(&self) -> Option<ProviderVideo> {
+        self.imp().provider_video.borrow().clone()
+    }
+
+    /// The url identifying the video.
+    pub fn url(&self) -> String {
+        self.property::<Option<String>>("url").unwrap_or_default()
+    }
+
+    /// Whether both objects represent the same video.
+    pub fn same_video(&self, other: &VideoObject) -> bool {
+        self.property::<Option<String>>("url") == other.property::<Option<String>>("url")
+    }
+
+    pub fn matches(&self, query: &Query) -> bool {
+        query.is_empty()
+            || query.matches(
+                &self.property::<Option<String>>("title").unwrap_or_default(),
+                &self
+                    .property::<Option<String>>("author")
+                    .unwrap_or_default(),
+                &self
+                    .property::<Option<String>>("platform")
+                    .unwrap_or_default(),
+            )
+    }
+
+    /// The additional metadata of the video, if known.
+    pub fn metadata(&self) -> Option<VideoMetadata> {
+        self.imp().metadata.borrow().clone()
+    }
+
+    /// The duration in seconds, if known.
+    pub fn duration(&self) -> Option<i64> {
+        self.metadata().and_then(|m| m.duration)
+    }
+
+    /// The kind of the video, videos without metadata are considered normal videos.
+    pub fn kind(&self) -> VideoKind {
+        self.metadata().map(|m| m.kind).unwrap_or_default()
+    }
+
+    fn set_metadata_properties(&self) {
+        if let Some(metadata) = self.metadata() {
+            if !metadata.kind.is_livestream() {
+                self.set_property("duration", metadata.duration.map(format_duration));
+            }
+            self.set_property("views", metadata.views.map(format_views));
+            self.set_property("kind", format_kind(metadata.kind));
+        }
+    }
+
     pub fn uploaded(&self) -> Option<chrono::NaiveDateTime> {
-        self.video().map(|v| v.uploaded())
+        self.video()
+            .map(|v| v.uploaded())
+            .or_else(|| self.provider_video().map(|v| v.uploaded))
     }
 
+    /// The local file if the video was downloaded, the url otherwise.
+    fn playable_url(&self) -> String {
+        self.property::<Option<String>>("local-path")
+            .unwrap_or_else(|| self.property::<Option<String>>("url").unwrap_or_default())
+    }
+
+    /// The values of the placeholders of the player and downloader commands.
+    pub fn placeholders(&self) -> Placeholders {
+        Placeholders {
+            url: self.property("url"),
+            title: self.property("title"),
+            author: self.property("author"),
+            platform: self.property("platform"),
+            local_path: self.property("local-path"),
+        }
+    }
+
+    /// Play the video with the default player, resuming where it was left off the last time.
     pub fn play(&self) {
+        let platform = self.property::<Option<String>>("platform");
+        self.play_resumed(&profiles::command(platform.as_deref()));
+    }
+
+    /// Play the video with the player profile with the name, the player without a profile for
+    /// the empty name.
+    pub fn open_with(&self, profile: &str) {
+        self.play_resumed(&profiles::command_of(profile));
+    }
+
+    fn play_resumed(&self, player: &str) {
+        let start = self
+            .property::<Option<String>>("url")
+            .and_then(|url| resume_position(&url))
+            .map(|r| r.position);
+        self.play_with(player, self.placeholders(), start);
+    }
+
+    /// Remember that the video was watched.
+    pub fn mark_watched(&self) {
+        if let Some(url) = self.property::<Option<String>>("url") {
+            crate::watched::set_watched(&url);
+        }
+        self.set_property("watched", true);
+        self.set_progress(0.0);
+    }
+
+    /// Play the video starting at the given time in seconds.
+    pub fn play_at(&self, seconds: i64) {
+        let url = self.property::<Option<String>>("url").unwrap_or_default();
+        let platform = self.video().map(|v| v.platform());
+        let player = profiles::command(self.property::<Option<String>>("platform").as_deref());
+        let values = Placeholders {
+            url: Some(crate::details::url_at(&url, platform, seconds)),
+            // The local file would not start at the given time.
+            local_path: None,
+            ..self.placeholders()
+        };
+        self.play_with(&player, values, None);
+    }
+
+    fn play_with(&self, player: &str, values: Placeholders, start: Option<f64>) {
         self.set_property("playing", true);
         let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+        let progress_sender = sender.clone();
         play(
-            self.property::<Option<String>>("local-path")
-                .unwrap_or_else(|| self.property::<Option<String>>("url").unwrap_or_default()),
-            move || {
-                let _ = sender.send(());
+            player,
+            values,
+            start,
+            move |event| {
+                let _ = progress_sender.send(PlayerMessage::Event(event));
+            },
+            move |result| {
+                let _ = sender.send(PlayerMessage::Exited(result));
             },
         );
+        let resume = Cell::new(ResumePosition {
+            position: start.unwrap_or_default(),
+            duration: None,
+        });
+        let finished = Cell::new(false);
         receiver.attach(
             None,
-            clone!(@weak self as s => @default-return Continue(false), move |_| {
-                s.set_property("playing", false);
+            clone!(@weak self as s => @default-return Continue(false), move |message| {
+                let mut current = resume.get();
+                match message {
+                    PlayerMessage::Event(mpv::Event::Position(position)) => current.position = position,
+                    PlayerMessage::Event(mpv::Event::Duration(duration)) => current.duration = Some(duration),
+                    PlayerMessage::Event(mpv::Event::Pause(paused)) => s.set_property("paused", paused),
+                    PlayerMessage::Event(mpv::Event::EndOfFile) => finished.set(true),
+                    PlayerMessage::Event(mpv::Event::PlaylistPosition(_)) => {}
+                    PlayerMessage::Exited(result) => {
+                        s.set_property("playing", false);
+                        s.set_property("paused", false);
+                        s.playback_ended(finished.get(), current);
+                        if let Err(e) = result {
+                            command_error::show(&gettextrs::gettext("Failed to Play Video"), &e);
+                        }
+                        return Continue(false);
+                    }
+                }
+                resume.set(current);
+                if current.duration.is_some() {
+                    s.set_progress(current.progress());
+                }
                 Continue(true)
             }),
         );
     }
 
-    pub fn download(&self) {
-        self.set_property("downloading", true);
+    /// Start playing the video in the built-in player, returning the position in seconds to
+    /// resume at.
+    pub fn start_builtin(&self) -> Option<f64> {
+        self.set_property("playing", true);
+        self.property::<Option<String>>("url")
+            .and_then(|url| resume_position(&url))
+            .map(|r| r.position)
+    }
+
+    /// Report the playback in the built-in player.
+    pub fn update_builtin(&self, paused: bool, resume: ResumePosition) {
+        self.set_property("paused", paused);
+        if resume.duration.is_some() {
+            self.set_progress(resume.progress());
+        }
+    }
+
+    /// Stop playing the video in the built-in player.
+    pub fn stop_builtin(&self, finished: bool, resume: ResumePosition) {
+        self.set_property("playing", false);
+        self.set_property("paused", false);
+        self.playback_ended(finished, resume);
+    }
+
+    /// Mark the video as watched if it was played until the end, remember where to resume it
+    /// otherwise.
+    fn playback_ended(&self, finished: bool, resume: ResumePosition) {
+        let url = match self.property::<Option<String>>("url") {
+            Some(url) => url,
+            None => return,
+        };
+        if finished {
+            self.mark_watched();
+        } else if resume.position >= RESUME_MIN_POSITION && resume.duration.is_some() {
+            set_resume_position(&url, Some(resume));
+        }
+    }
+
+    fn set_progress(&self, progress: f64) {
+        self.set_property("progress", progress);
+        self.notify("has-progress");
+    }
+
+    /// Play all videos in one player.
+    ///
+    /// If the player reports its playback, as mpv does, a video is marked as watched once it was
+    /// played until the end. Otherwise all videos are marked as watched once the player exited
+    /// successfully. `finished` is called with every video marked as watched.
+    pub fn play_all<F: Fn(&VideoObject) + 'static>(videos: Vec<VideoObject>, finished: F) {
+        if videos.is_empty() {
+            return;
+        }
+        let urls = videos.iter().map(|v| v.playable_url()).collect();
         let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
-        download(
-            self.property::<Option<String>>("url").unwrap_or_default(),
-            move |local_path| {
-                let _ = sender.send(local_path);
+        let progress_sender = sender.clone();
+        play_playlist(
+            &profiles::command(None),
+            urls,
+            move |event| {
+                let _ = progress_sender.send(PlayerMessage::Event(event));
+            },
+            move |result| {
+                let _ = sender.send(PlayerMessage::Exited(result));
             },
         );
+        let current: Cell<Option<usize>> = Cell::new(None);
+        let tracked = Cell::new(false);
+        let mark_finished = move |video: &VideoObject| {
+            video.mark_watched();
+            finished(video);
+        };
+        receiver.attach(None, move |message| match message {
+            PlayerMessage::Event(event) => {
+                tracked.set(true);
+                match event {
+                    mpv::Event::PlaylistPosition(index) if index < videos.len() => {
+                        if let Some(previous) = current.replace(Some(index)) {
+                            videos[previous].set_property("playing", false);
+                        }
+                        videos[index].set_property("playing", true);
+                    }
+                    mpv::Event::EndOfFile => {
+                        if let Some(index) = current.get() {
+                            mark_finished(&videos[index]);
+                        }
+                    }
+                    _ => {}
+                }
+                Continue(true)
+            }
+            PlayerMessage::Exited(result) => {
+                if let Some(index) = current.take() {
+                    videos[index].set_property("playing", false);
+                }
+                match result {
+                    Ok(_) if !tracked.get() => videos.iter().for_each(&mark_finished),
+                    Ok(_) => {}
+                    Err(e) => command_error::show(&gettextrs::gettext("Failed to Play Queue"), &e),
+                }
+                Continue(false)
+            }
+        });
+    }
+
+    pub fn download(&self) {
+        self.set_property("downloading", true);
+        let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+        let values = Placeholders {
+            // Download the video again instead of the local file.
+            local_path: None,
+            ..self.placeholders()
+        };
+        download(values, move |result| {
+            let _ = sender.send(result);
+        });
         receiver.attach(
             None,
-            clone!(@weak self as s => @default-return Continue(false), move |local_path| {
+            clone!(@weak self as s => @default-return Continue(false), move |result| {
                 s.set_property("downloading", false);
-                s.set_property("local-path", local_path);
-                s.notify("is-local");
-                Continue(true)
+                match result {
+                    Ok(local_path) => {
+                        s.set_property("local-path", local_path);
+                        s.notify("is-local");
+                    }
+                    Err(e) => command_error::show(&gettextrs::gettext("Failed to Download Video"), &e),
+                }
+                Continue(false)
             }),
         );
     }
@@ -147,9 +491,12 @@ mod imp {
     use std::cell::{Cell, RefCell};
     use tf_join::AnyVideo;
 
+    use crate::metadata::VideoMetadata;
+    use crate::provider::ProviderVideo;
+
     use gdk::{
-        glib::{ParamSpec, ParamSpecBoolean, ParamSpecString, Value},
-        prelude::ToValue,
+        glib::{ParamSpec, ParamSpecBoolean, ParamSpecDouble, ParamSpecString, Value},
+        prelude::{ParamSpecBuilderExt, ToValue},
         subclass::prelude::{ObjectImpl, ObjectSubclass},
     };
     use once_cell::sync::Lazy;
@@ -163,11 +510,22 @@ mod imp {
         url: RefCell<Option<String>>,
         thumbnail_url: RefCell<Option<String>>,
         local_path: RefCell<Option<String>>,
+        group_header: RefCell<Option<String>>,
+        duration: RefCell<Option<String>>,
+        views: RefCell<Option<String>>,
+        kind: RefCell<Option<String>>,
 
         playing: Cell<bool>,
         downloading: Cell<bool>,
+        is_new: Cell<bool>,
+        divider: Cell<bool>,
+        watched: Cell<bool>,
+        paused: Cell<bool>,
+        progress: Cell<f64>,
 
         pub(super) video: RefCell<Option<AnyVideo>>,
+        pub(super) provider_video: RefCell<Option<ProviderVideo>>,
+        pub(super) metadata: RefCell<Option<VideoMetadata>>,
     }
 
     #[glib::object_subclass]
@@ -188,9 +546,24 @@ mod imp {
                     str_prop!("platform"),
                     str_prop!("date"),
                     str_prop!("local-path"),
+                    str_prop!("group-header"),
+                    str_prop!("duration"),
+                    str_prop!("views"),
+                    str_prop!("kind"),
                     ParamSpecBoolean::builder("playing").build(),
                     ParamSpecBoolean::builder("downloading").build(),
                     ParamSpecBoolean::builder("is-local").build(),
+                    ParamSpecBoolean::builder("is-new").build(),
+                    ParamSpecBoolean::builder("divider").build(),
+                    ParamSpecBoolean::builder("watched").build(),
+                    ParamSpecBoolean::builder("paused").build(),
+                    ParamSpecDouble::builder("progress")
+                        .minimum(0.0)
+                        .maximum(1.0)
+                        .build(),
+                    ParamSpecBoolean::builder("has-progress")
+                        .read_only()
+                        .build(),
                 ]
             });
             PROPERTIES.as_ref()
@@ -207,6 +580,31 @@ mod imp {
                     .set(value.get().expect("Expect 'downloading' to be a boolean."));
                 return;
             }
+            if pspec.name() == "is-new" {
+                self.is_new
+                    .set(value.get().expect("Expect 'is-new' to be a boolean."));
+                return;
+            }
+            if pspec.name() == "divider" {
+                self.divider
+                    .set(value.get().expect("Expect 'divider' to be a boolean."));
+                return;
+            }
+            if pspec.name() == "watched" {
+                self.watched
+                    .set(value.get().expect("Expect 'watched' to be a boolean."));
+                return;
+            }
+            if pspec.name() == "paused" {
+                self.paused
+                    .set(value.get().expect("Expect 'paused' to be a boolean."));
+                return;
+            }
+            if pspec.name() == "progress" {
+                self.progress
+                    .set(value.get().expect("Expect 'progress' to be a double."));
+                return;
+            }
             prop_set_all!(
                 value,
                 pspec,
@@ -223,7 +621,15 @@ mod imp {
                 "date",
                 self.date,
                 "local-path",
-                self.local_path
+                self.local_path,
+                "group-header",
+                self.group_header,
+                "duration",
+                self.duration,
+                "views",
+                self.views,
+                "kind",
+                self.kind
             );
         }
 
@@ -237,6 +643,24 @@ mod imp {
             if pspec.name() == "is-local" {
                 return self.local_path.borrow().is_some().to_value();
             }
+            if pspec.name() == "is-new" {
+                return self.is_new.get().to_value();
+            }
+            if pspec.name() == "divider" {
+                return self.divider.get().to_value();
+            }
+            if pspec.name() == "watched" {
+                return self.watched.get().to_value();
+            }
+            if pspec.name() == "paused" {
+                return self.paused.get().to_value();
+            }
+            if pspec.name() == "progress" {
+                return self.progress.get().to_value();
+            }
+            if pspec.name() == "has-progress" {
+                return (self.progress.get() > 0.0).to_value();
+            }
             prop_get_all!(
                 pspec,
                 "title",
@@ -252,7 +676,15 @@ mod imp {
                 "date",
                 self.date,
                 "local-path",
-                self.local_path
+                self.local_path,
+                "group-header",
+                self.group_header,
+                "duration",
+                self.duration,
+                "views",
+                self.views,
+                "kind",
+                self.kind
             )
         }
     }
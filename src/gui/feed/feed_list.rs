@@ -19,24 +19,56 @@
  */
 
 use std::cmp::min;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 
 use gdk::{
-    gio::{SimpleAction, SimpleActionGroup},
+    gio::{Settings, SimpleAction, SimpleActionGroup},
     glib,
     glib::clone,
-    prelude::{ActionMapExt, ListModelExt, ObjectExt, ToValue},
+    prelude::{ActionMapExt, Cast, ListModelExt, ObjectExt, SettingsExt, ToValue},
     subclass::prelude::ObjectSubclassIsExt,
 };
 use gtk::{
     traits::{AdjustmentExt, WidgetExt},
-    Adjustment,
+    Adjustment, CallbackAction, Shortcut, ShortcutController, ShortcutTrigger, Widget,
 };
 use tf_join::AnyVideo;
 use tf_playlist::PlaylistManager;
 
+use super::date_range::{self, DateRange};
+use super::feed_item::FeedItem;
 use super::feed_item_object::VideoObject;
+use super::sorting::{self, Grouping, SortOrder};
+use crate::metadata::VideoKind;
+use crate::search::Query;
 
 const LOAD_COUNT: usize = 10;
+/// The width from which on the videos are shown in a grid if the layout is automatic.
+const GRID_BREAKPOINT: i32 = 900;
+
+/// How the videos are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    /// A grid on wide windows, a list otherwise.
+    #[default]
+    Automatic,
+    List,
+    Grid,
+}
+
+impl FromStr for Layout {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "automatic" => Ok(Layout::Automatic),
+            "list" => Ok(Layout::List),
+            "grid" => Ok(Layout::Grid),
+            _ => Err(()),
+        }
+    }
+}
 
 gtk::glib::wrapper! {
     pub struct FeedList(ObjectSubclass<imp::FeedList>)
@@ -68,11 +100,92 @@ impl FeedList {
         actions.add_action(&action_more);
     }
 
+    /// Activate an action of the focused item of the list or grid, e.g. `item.download`.
+    fn activate_focused_item(view: &Widget, action: &str) -> bool {
+        let item = view
+            .focus_child()
+            .and_then(|r| r.first_child())
+            .and_then(|c| c.downcast::<FeedItem>().ok());
+        match item {
+            Some(item) => WidgetExt::activate_action(&item, action, None).is_ok(),
+            None => false,
+        }
+    }
+
+    fn setup_shortcuts(&self) {
+        let imp = self.imp();
+        for view in [
+            imp.feed_list.upcast_ref::<Widget>(),
+            imp.feed_grid.upcast_ref(),
+        ] {
+            let controller = ShortcutController::new();
+            for (accelerator, action) in [
+                ("<Control>p", "item.play"),
+                ("<Control>i", "item.details"),
+                ("<Control>d", "item.download"),
+                ("<Control>l", "item.watch-later"),
+                ("<Control>c", "item.clipboard"),
+            ] {
+                let callback =
+                    CallbackAction::new(move |view, _| Self::activate_focused_item(view, action));
+                controller.add_shortcut(Shortcut::new(
+                    ShortcutTrigger::parse_string(accelerator),
+                    Some(callback),
+                ));
+            }
+            view.add_controller(controller);
+        }
+    }
+
+    /// Switch between the list and the grid depending on the configured layout and the available
+    /// width.
+    fn update_layout(&self) {
+        let imp = self.imp();
+        let grid = match imp.layout.get() {
+            Layout::Automatic => imp.width.get() >= GRID_BREAKPOINT,
+            Layout::List => false,
+            Layout::Grid => true,
+        };
+        imp.view_stack
+            .set_visible_child_name(if grid { "grid" } else { "list" });
+    }
+
+    /// Keep the layout in sync with the one configured in the settings.
+    fn bind_layout_settings(&self) {
+        let settings = Settings::new(crate::config::APP_ID);
+        let imp = self.imp();
+        imp.layout
+            .set(settings.string("feed-layout").parse().unwrap_or_default());
+        settings.connect_changed(
+            Some("feed-layout"),
+            clone!(@weak self as s => move |settings, _| {
+                s.imp().layout.set(settings.string("feed-layout").parse().unwrap_or_default());
+                s.update_layout();
+            }),
+        );
+        imp.layout_settings.replace(Some(settings));
+        self.update_layout();
+    }
+
+    /// Show the search bar if it is hidden, hide it otherwise.
+    pub fn toggle_search(&self) {
+        let search_mode: bool = self.property("search-mode");
+        self.set_property("search-mode", !search_mode);
+    }
+
     fn setup_autoload(&self) {
-        let adj = self.imp().scrolled_window.vadjustment();
-        adj.connect_changed(clone!(@weak self as s => move |adj| {
-            s.load_if_screen_not_filled(adj);
-        }));
+        let imp = self.imp();
+        for scrolled_window in [imp.scrolled_window.get(), imp.scrolled_grid.get()] {
+            let adj = scrolled_window.vadjustment();
+            adj.connect_changed(
+                clone!(@weak self as s, @weak scrolled_window => move |adj| {
+                    // Only the visible view determines whether the screen is filled.
+                    if scrolled_window.is_mapped() {
+                        s.load_if_screen_not_filled(adj);
+                    }
+                }),
+            );
+        }
     }
 
     fn load_if_screen_not_filled(&self, adj: &Adjustment) {
@@ -82,7 +195,125 @@ impl FeedList {
         }
     }
 
-    pub fn set_items(&self, new_items: Vec<VideoObject>) {
+    fn is_visible(&self, item: &VideoObject) -> bool {
+        let imp = self.imp();
+        let kind = item.kind();
+        item.matches(&imp.query.borrow())
+            && imp.date_range.get().contains(item.uploaded())
+            && !(imp.hide_shorts.get() && kind == VideoKind::Short)
+            && !(imp.hide_livestreams.get() && kind.is_livestream())
+    }
+
+    fn filter(&self, items: &[VideoObject]) -> Vec<VideoObject> {
+        items
+            .iter()
+            .filter(|i| self.is_visible(i))
+            .cloned()
+            .collect()
+    }
+
+    /// Only show the items uploaded in the given range.
+    pub fn set_date_range(&self, date_range: DateRange) {
+        if date_range == self.imp().date_range.get() {
+            return;
+        }
+        self.imp().date_range.set(date_range);
+        let items = self.filter(&self.imp().all_items.borrow());
+        self.show_items(items);
+    }
+
+    /// Hide shorts and livestreams, including upcoming ones.
+    pub fn set_hidden_kinds(&self, hide_shorts: bool, hide_livestreams: bool) {
+        let imp = self.imp();
+        if imp.hide_shorts.get() == hide_shorts && imp.hide_livestreams.get() == hide_livestreams {
+            return;
+        }
+        imp.hide_shorts.set(hide_shorts);
+        imp.hide_livestreams.set(hide_livestreams);
+        let items = self.filter(&imp.all_items.borrow());
+        self.show_items(items);
+    }
+
+    /// Keep the date range and hidden kinds of videos in sync with the ones configured in the
+    /// settings.
+    pub fn bind_filter_settings(&self) {
+        let settings = Settings::new(crate::config::APP_ID);
+        self.set_date_range(DateRange::from_settings(&settings));
+        for key in date_range::SETTINGS_KEYS {
+            settings.connect_changed(
+                Some(key),
+                clone!(@weak self as s => move |settings, _| {
+                    s.set_date_range(DateRange::from_settings(settings));
+                }),
+            );
+        }
+        self.set_hidden_kinds(
+            settings.boolean("hide-shorts"),
+            settings.boolean("hide-livestreams"),
+        );
+        for key in ["hide-shorts", "hide-livestreams"] {
+            settings.connect_changed(
+                Some(key),
+                clone!(@weak self as s => move |settings, _| {
+                    s.set_hidden_kinds(
+                        settings.boolean("hide-shorts"),
+                        settings.boolean("hide-livestreams"),
+                    );
+                }),
+            );
+        }
+        self.imp().settings.replace(Some(settings));
+    }
+
+    /// Filter the items of the list by the given query.
+    pub fn set_query(&self, query: Query) {
+        if query == *self.imp().query.borrow() {
+            return;
+        }
+        self.imp().query.replace(query);
+        let items = self.filter(&self.imp().all_items.borrow());
+        self.show_items(items);
+    }
+
+    /// Sort and group the items, by default the items are kept in the order they were given.
+    pub fn set_sorting(&self, order: SortOrder, grouping: Grouping) {
+        let imp = self.imp();
+        imp.sort_order.set(Some(order));
+        imp.grouping.set(grouping);
+        let mut all_items = imp.all_items.take();
+        self.sort(&mut all_items);
+        let items = self.filter(&all_items);
+        imp.all_items.replace(all_items);
+        self.show_items(items);
+    }
+
+    fn sort(&self, items: &mut [VideoObject]) {
+        if let Some(order) = self.imp().sort_order.get() {
+            sorting::sort(items, order, self.imp().grouping.get());
+        }
+    }
+
+    fn update_group_headers(&self) {
+        let imp = self.imp();
+        sorting::set_group_headers(&imp.items.borrow(), imp.grouping.get());
+    }
+
+    /// Replace all items, keeping the urls of the items in sync.
+    fn set_all_items(&self, items: Vec<VideoObject>) {
+        let imp = self.imp();
+        imp.urls.replace(items.iter().map(|i| i.url()).collect());
+        imp.all_items.replace(items);
+    }
+
+    pub fn set_items(&self, mut new_items: Vec<VideoObject>) {
+        self.sort(&mut new_items);
+        let items = self.filter(&new_items);
+        self.set_all_items(new_items);
+        self.show_items(items);
+    }
+
+    /// Show the given items, which are already filtered, from the start.
+    fn show_items(&self, new_items: Vec<VideoObject>) {
         let imp = self.imp();
         let items = &imp.items;
         let model = &imp.model;
@@ -91,15 +322,170 @@ impl FeedList {
         let _ = items.replace(new_items);
         model.borrow().remove_all();
         loaded_count.set(0);
+        self.update_group_headers();
 
         let _ = self.activate_action("feed.more", None);
 
         self.set_more_available();
         self.notify("is-empty");
+        self.notify("no-results");
+    }
+
+    /// Update the items of the list without throwing away the items already shown.
+    ///
+    /// Items already in the list are kept (and their objects reused), items which are not in
+    /// `new_items` anymore are removed and new items are inserted at their position. If the order
+    /// of the items changed, the list will be rebuilt instead.
+    pub fn update_items(&self, new_items: Vec<VideoObject>) {
+        let imp = self.imp();
+
+        let new_items: Vec<VideoObject> = {
+            let mut existing: HashMap<String, VideoObject> = imp
+                .all_items
+                .borrow()
+                .iter()
+                .map(|i| (i.url(), i.clone()))
+                .collect();
+            new_items
+                .into_iter()
+                .map(|n| existing.remove(&n.url()).unwrap_or(n))
+                .collect()
+        };
+        let mut all_items = new_items;
+        self.sort(&mut all_items);
+        let new_items = self.filter(&all_items);
+        self.set_all_items(all_items);
+
+        let model = imp.model.borrow().clone();
+        let loaded: Vec<VideoObject> = (0..model.n_items())
+            .filter_map(|i| model.item(i).and_then(|o| o.downcast().ok()))
+            .collect();
+        let new_set: HashSet<&VideoObject> = new_items.iter().collect();
+        let kept: Vec<&VideoObject> = loaded.iter().filter(|l| new_set.contains(l)).collect();
+        let kept_set: HashSet<&VideoObject> = kept.iter().copied().collect();
+        let kept_in_new: Vec<&VideoObject> =
+            new_items.iter().filter(|n| kept_set.contains(n)).collect();
+        if kept != kept_in_new {
+            self.show_items(new_items);
+            return;
+        }
+
+        for (idx, item) in loaded.iter().enumerate().rev() {
+            if !new_set.contains(item) {
+                model.remove(idx as u32);
+            }
+        }
+
+        // The kept items are in the same order as in `new_items`, insert the new items in between.
+        // New items after the last loaded item will be loaded on demand.
+        for (position, item) in new_items.iter().enumerate() {
+            let position = position as u32;
+            if position >= model.n_items() {
+                break;
+            }
+            let at_position = model
+                .item(position)
+                .and_then(|o| o.downcast::<VideoObject>().ok());
+            if at_position.as_ref() != Some(item) {
+                model.insert(position, item);
+            }
+        }
+
+        imp.loaded_count.set(model.n_items() as usize);
+        let _ = imp.items.replace(new_items);
+        self.update_group_headers();
+
+        if imp.loaded_count.get() == 0 {
+            let _ = self.activate_action("feed.more", None);
+        }
+        self.set_more_available();
+        self.notify("is-empty");
+        self.notify("no-results");
+    }
+
+    /// Insert the items at their sorted position, items already in the list are ignored.
+    ///
+    /// Only the items inserted in front of the last loaded item are loaded immediately, the
+    /// others will be loaded on demand.
+    pub fn insert_items(&self, new_items: Vec<VideoObject>) {
+        let imp = self.imp();
+
+        let mut new_items: Vec<VideoObject> = {
+            let mut urls = imp.urls.borrow_mut();
+            new_items
+                .into_iter()
+                .filter(|n| urls.insert(n.url()))
+                .collect()
+        };
+        if new_items.is_empty() {
+            return;
+        }
+        self.sort(&mut new_items);
+        let visible = self.filter(&new_items);
+        self.insert_sorted(&mut imp.all_items.borrow_mut(), new_items);
+        let positions = self.insert_sorted(&mut imp.items.borrow_mut(), visible);
+
+        // The loaded items are the start of the items, the new items in this range are inserted.
+        let model = imp.model.borrow().clone();
+        let mut loaded_count = imp.loaded_count.get();
+        for &position in &positions {
+            if position >= loaded_count {
+                break;
+            }
+            let item = imp.items.borrow()[position].clone();
+            model.insert(position as u32, &item);
+            loaded_count += 1;
+        }
+
+        imp.loaded_count.set(loaded_count);
+        sorting::update_group_headers(&imp.items.borrow(), &positions, imp.grouping.get());
+
+        if loaded_count < LOAD_COUNT {
+            let _ = self.activate_action("feed.more", None);
+        }
+        self.set_more_available();
+        self.notify("is-empty");
+        self.notify("no-results");
+    }
+
+    /// Insert the sorted new items into the sorted items, without a sort order they are appended.
+    /// Returns the positions the new items were inserted at, in ascending order.
+    fn insert_sorted(
+        &self,
+        items: &mut Vec<VideoObject>,
+        new_items: Vec<VideoObject>,
+    ) -> Vec<usize> {
+        let imp = self.imp();
+        match imp.sort_order.get() {
+            Some(order) => sorting::insert_sorted(items, new_items, order, imp.grouping.get()),
+            None => {
+                let start = items.len();
+                items.extend(new_items);
+                (start..items.len()).collect()
+            }
+        }
+    }
+
+    /// Whether the list contains the video with the url, even if it is filtered out.
+    pub fn contains(&self, url: &str) -> bool {
+        self.imp().urls.borrow().contains(url)
+    }
+
+    /// All items of the list, including the ones not yet loaded or filtered out.
+    pub fn items(&self) -> Vec<VideoObject> {
+        self.imp().all_items.borrow().clone()
     }
 
     pub fn prepend(&self, new_item: VideoObject) {
         let imp = self.imp();
+        imp.all_items.borrow_mut().insert(0, new_item.clone());
+        imp.urls.borrow_mut().insert(new_item.url());
+        if !self.is_visible(&new_item) {
+            self.notify("is-empty");
+            self.notify("no-results");
+            return;
+        }
+
         let items = &imp.items;
         let model = &imp.model;
         let loaded_count = &imp.loaded_count;
@@ -107,15 +493,21 @@ impl FeedList {
         let _ = items.borrow_mut().insert(0, new_item.clone());
         model.borrow_mut().insert(0, &new_item);
         loaded_count.set(loaded_count.get() + 1);
+        self.update_group_headers();
 
         self.set_more_available();
         self.notify("is-empty");
+        self.notify("no-results");
     }
 
     pub fn remove(&self, new_item: VideoObject) {
         // Extra block needed to end the mutable borrow of `items`.
         {
             let imp = self.imp();
+            imp.all_items
+                .borrow_mut()
+                .retain(|i| i.video() != new_item.video());
+            imp.urls.borrow_mut().remove(&new_item.url());
             let mut items = imp.items.borrow_mut();
             let model = &imp.model;
             let loaded_count = &imp.loaded_count;
@@ -129,9 +521,11 @@ impl FeedList {
                 items.remove(idx);
             }
         }
+        self.update_group_headers();
 
         self.set_more_available();
         self.notify("is-empty");
+        self.notify("no-results");
     }
 
     pub fn set_playlist_manager(&self, playlist_manager: PlaylistManager<String, AnyVideo>) {
@@ -150,8 +544,11 @@ impl FeedList {
 
 pub mod imp {
     use std::cell::{Cell, RefCell};
+    use std::collections::HashSet;
 
     use gdk::gio::ListStore;
+    use gdk::gio::Settings;
+    use gdk::glib::clone;
     use gdk::glib::ParamSpec;
     use gdk::glib::ParamSpecBoolean;
     use gdk::glib::Value;
@@ -168,8 +565,14 @@ pub mod imp {
     use tf_join::AnyVideo;
     use tf_playlist::PlaylistManager;
 
+    use super::Layout;
+    use crate::gui::feed::date_range::DateRange;
     use crate::gui::feed::feed_item::FeedItem;
     use crate::gui::feed::feed_item_object::VideoObject;
+    use crate::gui::feed::sorting::{Grouping, SortOrder};
+    use crate::gui::utility::Utility;
+    use crate::gui::window::Window;
+    use crate::search::Query;
 
     #[derive(CompositeTemplate, Default)]
     #[template(resource = "/ui/feed_list.ui")]
@@ -178,7 +581,22 @@ pub mod imp {
         pub(super) feed_list: TemplateChild<gtk::ListView>,
         #[template_child]
         pub(super) scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub(super) feed_grid: TemplateChild<gtk::GridView>,
+        #[template_child]
+        pub(super) scrolled_grid: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub(super) view_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub(super) search_bar: TemplateChild<gtk::SearchBar>,
+        #[template_child]
+        pub(super) search_entry: TemplateChild<gtk::SearchEntry>,
 
+        /// All items, including the ones not matching the query.
+        pub(super) all_items: RefCell<Vec<VideoObject>>,
+        /// The urls of all items.
+        pub(super) urls: RefCell<HashSet<String>>,
+        /// The items matching the query.
         pub(super) items: RefCell<Vec<VideoObject>>,
         pub(super) model: RefCell<ListStore>,
         pub(super) loaded_count: Cell<usize>,
@@ -186,16 +604,46 @@ pub mod imp {
         pub(super) playlist_manager: RefCell<Option<PlaylistManager<String, AnyVideo>>>,
 
         pub(super) more_available: Cell<bool>,
+        pub(super) query: RefCell<Query>,
+        pub(super) sort_order: Cell<Option<SortOrder>>,
+        pub(super) grouping: Cell<Grouping>,
+        pub(super) date_range: Cell<DateRange>,
+        pub(super) hide_shorts: Cell<bool>,
+        pub(super) hide_livestreams: Cell<bool>,
+        pub(super) settings: RefCell<Option<Settings>>,
+        pub(super) layout: Cell<Layout>,
+        /// The width the list was last allocated.
+        pub(super) width: Cell<i32>,
+        pub(super) layout_settings: RefCell<Option<Settings>>,
+        search_mode: Cell<bool>,
     }
 
     impl FeedList {
         pub(super) fn setup(&self) {
             let model = gtk::gio::ListStore::new(VideoObject::static_type());
             let selection_model = gtk::NoSelection::new(Some(model.clone()));
+            // The list and the grid share the same model.
             self.feed_list.get().set_model(Some(&selection_model));
+            self.feed_grid.get().set_model(Some(&selection_model));
 
             self.model.replace(model);
 
+            self.feed_list.set_factory(Some(&self.factory(false)));
+            self.feed_list.set_single_click_activate(true);
+            self.feed_list.connect_activate(|list_view, position| {
+                Self::activate(list_view.upcast_ref(), list_view.model(), position);
+            });
+
+            self.feed_grid.set_factory(Some(&self.factory(true)));
+            self.feed_grid.set_single_click_activate(true);
+            self.feed_grid.connect_activate(|grid_view, position| {
+                Self::activate(grid_view.upcast_ref(), grid_view.model(), position);
+            });
+
+            self.obj().setup_autoload();
+        }
+
+        fn factory(&self, grid: bool) -> SignalListItemFactory {
             let factory = SignalListItemFactory::new();
             let playlist_manager = self
                 .playlist_manager
@@ -204,27 +652,49 @@ pub mod imp {
                 .expect("PlaylistManager should be set up");
             factory.connect_setup(move |_, list_item| {
                 let feed_item = FeedItem::new(playlist_manager.clone());
+                feed_item.set_grid(grid);
                 list_item.set_child(Some(&feed_item));
 
                 list_item
                     .property_expression("item")
                     .bind(&feed_item, "video", Widget::NONE);
             });
-            self.feed_list.set_factory(Some(&factory));
-            self.feed_list.set_single_click_activate(true);
-
-            self.feed_list.connect_activate(move |list_view, position| {
-                let model = list_view.model().expect("The model has to exist.");
-                let video_object = model
-                    .item(position)
-                    .expect("The item has to exist.")
-                    .downcast::<VideoObject>()
-                    .expect("The item has to be an `VideoObject`.");
+            factory
+        }
 
-                video_object.play();
-            });
+        fn activate(view: &Widget, model: Option<gtk::SelectionModel>, position: u32) {
+            let model = model.expect("The model has to exist.");
+            let video_object = model
+                .item(position)
+                .expect("The item has to exist.")
+                .downcast::<VideoObject>()
+                .expect("The item has to be an `VideoObject`.");
+
+            match view.root().and_then(|r| r.downcast::<Window>().ok()) {
+                Some(window) => window.activate_video(&video_object),
+                None => video_object.play(),
+            }
+        }
 
-            self.obj().setup_autoload();
+        fn setup_search(&self) {
+            let obj = self.obj();
+            let entry = self.search_entry.get();
+            self.search_bar.connect_entry(&entry);
+            self.search_bar.set_key_capture_widget(Some(&*obj));
+            self.search_bar
+                .bind_property("search-mode-enabled", &*obj, "search-mode")
+                .flags(glib::BindingFlags::SYNC_CREATE | glib::BindingFlags::BIDIRECTIONAL)
+                .build();
+            self.search_bar
+                .connect_search_mode_enabled_notify(clone!(@weak entry => move |bar| {
+                    if !bar.is_search_mode() {
+                        entry.set_text("");
+                    }
+                }));
+            self.search_entry
+                .connect_search_changed(clone!(@weak obj => move |entry| {
+                    obj.set_query(Query::parse(entry.text()));
+                }));
         }
     }
 
@@ -251,6 +721,7 @@ pub mod imp {
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
             Self::bind_template_callbacks(klass);
+            Utility::bind_template_callbacks(klass);
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -262,6 +733,9 @@ pub mod imp {
         fn constructed(&self) {
             self.parent_constructed();
             self.obj().add_actions();
+            self.obj().setup_shortcuts();
+            self.obj().bind_layout_settings();
+            self.setup_search();
         }
 
         fn properties() -> &'static [ParamSpec] {
@@ -269,6 +743,8 @@ pub mod imp {
                 vec![
                     ParamSpecBoolean::builder("more-available").build(),
                     ParamSpecBoolean::builder("is-empty").build(),
+                    ParamSpecBoolean::builder("no-results").build(),
+                    ParamSpecBoolean::builder("search-mode").build(),
                 ]
             });
             PROPERTIES.as_ref()
@@ -282,6 +758,10 @@ pub mod imp {
                         .expect("Property more-available of incorrect type");
                     self.more_available.replace(value);
                 }
+                "search-mode" => {
+                    let value: bool = value.get().expect("Property search-mode of incorrect type");
+                    self.search_mode.replace(value);
+                }
                 _ => unimplemented!(),
             }
         }
@@ -289,12 +769,25 @@ pub mod imp {
         fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
             match pspec.name() {
                 "more-available" => self.more_available.get().to_value(),
-                "is-empty" => (self.model.borrow().n_items() == 0).to_value(),
+                "is-empty" => self.all_items.borrow().is_empty().to_value(),
+                "no-results" => (!self.all_items.borrow().is_empty()
+                    && self.items.borrow().is_empty())
+                .to_value(),
+                "search-mode" => self.search_mode.get().to_value(),
                 _ => unimplemented!(),
             }
         }
     }
 
-    impl WidgetImpl for FeedList {}
+    impl WidgetImpl for FeedList {
+        fn size_allocate(&self, width: i32, height: i32, baseline: i32) {
+            self.parent_size_allocate(width, height, baseline);
+            if self.width.replace(width) != width {
+                // Switching the layout while allocating would require another allocation.
+                let obj = self.obj();
+                glib::idle_add_local_once(clone!(@weak obj => move || obj.update_layout()));
+            }
+        }
+    }
     impl BoxImpl for FeedList {}
 }
@@ -18,10 +18,13 @@
  *
  */
 
+use gdk::prelude::ObjectExt;
 use gdk::subclass::prelude::ObjectSubclassIsExt;
 use tf_join::{AnyVideo, Joiner};
 use tf_playlist::PlaylistManager;
 
+use crate::provider::ProviderSubscriptionList;
+
 gtk::glib::wrapper! {
     pub struct FeedPage(ObjectSubclass<imp::FeedPage>)
         @extends gtk::Box, gtk::Widget,
@@ -30,25 +33,61 @@ gtk::glib::wrapper! {
 }
 
 impl FeedPage {
-    pub fn setup(&self, playlist_manager: PlaylistManager<String, AnyVideo>, joiner: Joiner) {
+    pub fn setup(
+        &self,
+        playlist_manager: PlaylistManager<String, AnyVideo>,
+        joiner: Joiner,
+        provider_subscription_list: ProviderSubscriptionList,
+    ) {
         self.imp().playlist_manager.replace(Some(playlist_manager));
         self.imp().joiner.replace(Some(joiner));
+        self.imp()
+            .provider_subscription_list
+            .replace(Some(provider_subscription_list));
         self.imp().setup(&self);
     }
 
     pub fn reload(&self) {
         self.imp().reload();
     }
+
+    /// Show or hide the search bar.
+    pub fn toggle_search(&self) {
+        self.imp().feed_list.toggle_search();
+    }
+
+    /// Mark all videos currently in the feed as seen.
+    pub fn mark_seen(&self) {
+        self.imp().mark_seen();
+    }
+
+    /// All videos in the feed, including the ones hidden by filters.
+    pub fn videos(&self) -> Vec<super::feed_item_object::VideoObject> {
+        self.imp().feed_list.items()
+    }
+
+    /// The video in the feed with the given url.
+    pub fn video(&self, url: &str) -> Option<super::feed_item_object::VideoObject> {
+        self.imp()
+            .feed_list
+            .items()
+            .into_iter()
+            .find(|v| v.property::<Option<String>>("url").as_deref() == Some(url))
+    }
 }
 
 pub mod imp {
     use std::cell::Cell;
     use std::cell::RefCell;
+    use std::collections::HashSet;
 
+    use gdk::gio::SimpleActionGroup;
     use gdk::glib::clone;
     use gdk::glib::MainContext;
     use gdk::glib::ParamSpec;
     use gdk::glib::ParamSpecBoolean;
+    use gdk::glib::Sender;
+    use gdk::glib::SourceId;
     use gdk::glib::PRIORITY_DEFAULT;
     use glib::subclass::InitializingObject;
     use gtk::gio::Settings;
@@ -60,16 +99,39 @@ pub mod imp {
     use gtk::CompositeTemplate;
     use once_cell::sync::Lazy;
     use tf_core::ErrorStore;
-    use tf_core::Generator;
+    use tf_core::Video;
+    use tf_join::AnySubscription;
     use tf_join::AnyVideo;
     use tf_join::Joiner;
     use tf_playlist::PlaylistManager;
 
     use crate::config::APP_ID;
+    use crate::gui::feed::date_range_button::DateRangeButton;
     use crate::gui::feed::error_label::ErrorLabel;
     use crate::gui::feed::feed_item_object::VideoObject;
     use crate::gui::feed::feed_list::FeedList;
+    use crate::gui::notification;
+    use crate::gui::subscription::subscription_item_object::SubscriptionObject;
     use crate::gui::utility::Utility;
+    use crate::gui::window::Window;
+    use crate::provider::ProviderSubscription;
+    use crate::provider::ProviderSubscriptionList;
+    use crate::provider::ProviderVideo;
+    use crate::provider::ProviderVideoFilters;
+    use crate::scheduler::Failure;
+    use crate::scheduler::Scheduler;
+
+    /// The progress of a reload, sent from the generation of the feed.
+    enum ReloadEvent {
+        /// The videos of a single subscription were generated.
+        Generated(Vec<AnyVideo>, Vec<ProviderVideo>),
+        /// A single subscription failed to generate.
+        Failed(Failure<AnySubscription>),
+        /// A single provider subscription failed to generate.
+        ProviderFailed(Failure<ProviderSubscription>),
+        /// All subscriptions were generated.
+        Finished,
+    }
 
     #[derive(CompositeTemplate)]
     #[template(resource = "/ui/feed_page.ui")]
@@ -85,11 +147,42 @@ pub mod imp {
         #[template_child]
         pub(super) error_label: TemplateChild<ErrorLabel>,
 
+        #[template_child]
+        pub(super) progress_reload: TemplateChild<gtk::ProgressBar>,
+
+        #[template_child]
+        pub(super) revealer_new: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub(super) label_new: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub(super) btn_mark_seen: TemplateChild<gtk::Button>,
+
         reloading: Cell<bool>,
+        /// Whether to reload again once the current reload finished.
+        reload_queued: Cell<bool>,
+        /// The number of subscriptions already generated in the current reload.
+        loaded_subscriptions: Cell<usize>,
+        total_subscriptions: Cell<usize>,
+        /// The urls of the items of the feed before the current reload.
+        previous_urls: RefCell<HashSet<String>>,
+        /// The urls of the videos generated in the current reload.
+        generated_urls: RefCell<HashSet<String>>,
+        /// The subscriptions which failed to generate in the current reload.
+        failed_subscriptions: RefCell<HashSet<AnySubscription>>,
+        failed_provider_subscriptions: RefCell<HashSet<ProviderSubscription>>,
+        /// Whether the current reload was started by the background refresh.
+        background_reload: Cell<bool>,
+        refresh_source: RefCell<Option<SourceId>>,
+        /// The time of the last visit as unix timestamp.
+        last_visit: Cell<i64>,
+        /// The urls of the videos in the feed at the last visit, if known.
+        seen_urls: RefCell<Option<HashSet<String>>>,
 
         pub(super) playlist_manager: RefCell<Option<PlaylistManager<String, AnyVideo>>>,
         pub(super) joiner: RefCell<Option<Joiner>>,
+        pub(super) provider_subscription_list: RefCell<Option<ProviderSubscriptionList>>,
         error_store: RefCell<ErrorStore>,
+        reload_sender: RefCell<Option<Sender<ReloadEvent>>>,
 
         pub settings: gtk::gio::Settings,
     }
@@ -101,10 +194,27 @@ pub mod imp {
                 btn_reload: Default::default(),
                 btn_add_subscription: Default::default(),
                 error_label: Default::default(),
+                progress_reload: Default::default(),
+                revealer_new: Default::default(),
+                label_new: Default::default(),
+                btn_mark_seen: Default::default(),
                 reloading: Default::default(),
+                reload_queued: Default::default(),
+                loaded_subscriptions: Default::default(),
+                total_subscriptions: Default::default(),
+                previous_urls: Default::default(),
+                generated_urls: Default::default(),
+                failed_subscriptions: Default::default(),
+                failed_provider_subscriptions: Default::default(),
+                background_reload: Default::default(),
+                refresh_source: Default::default(),
+                last_visit: Default::default(),
+                seen_urls: Default::default(),
                 playlist_manager: Default::default(),
                 joiner: Default::default(),
+                provider_subscription_list: Default::default(),
                 error_store: Default::default(),
+                reload_sender: Default::default(),
                 settings: Settings::new(APP_ID),
             }
         }
@@ -115,47 +225,435 @@ pub mod imp {
             self.btn_reload.emit_clicked();
         }
 
+        /// Whether the video was uploaded since the last visit or was not in the feed at the last
+        /// visit, e.g. as it showed up late in the feed of its channel.
+        fn is_new(&self, video: &VideoObject) -> bool {
+            let last_visit = self.last_visit.get();
+            let uploaded = video
+                .uploaded()
+                .map(|d| d.timestamp() > last_visit)
+                .unwrap_or_default();
+            let unseen = self
+                .seen_urls
+                .borrow()
+                .as_ref()
+                .map(|seen| !seen.contains(&video.url()))
+                .unwrap_or_default();
+            uploaded || unseen
+        }
+
+        /// Highlight the videos uploaded since the last visit.
+        fn mark_new(&self) {
+            let items = self.feed_list.items();
+            let new_count = items.iter().filter(|v| self.is_new(v)).count();
+
+            // Only divide the new videos from the videos already seen if they are all in front.
+            let mut only_new_before = true;
+            for (i, item) in items.iter().enumerate() {
+                let item_is_new = self.is_new(item);
+                item.set_property("is-new", item_is_new);
+                item.set_property("divider", i > 0 && only_new_before && !item_is_new);
+                only_new_before &= item_is_new;
+            }
+
+            self.label_new.set_label(
+                &gettextrs::ngettext("{} new video", "{} new videos", new_count as u32)
+                    .replace("{}", &new_count.to_string()),
+            );
+            self.revealer_new.set_reveal_child(new_count > 0);
+        }
+
+        pub(super) fn mark_seen(&self) {
+            let seen = self.feed_list.items().iter().map(|v| v.url()).collect();
+            crate::watched::set_seen(&seen);
+            self.seen_urls.replace(Some(seen));
+            self.set_last_visit();
+            self.mark_new();
+        }
+
+        /// Set the last visit to now.
+        fn set_last_visit(&self) {
+            let now = chrono::Utc::now().naive_utc().timestamp();
+            self.last_visit.set(now);
+            if let Err(e) = self.settings.set_int64("last-visit", now) {
+                log::warn!("Failed to save the last visit: {}", e);
+            }
+        }
+
+        fn apply_sorting(&self) {
+            let order = self
+                .settings
+                .string("sort-order")
+                .parse()
+                .unwrap_or_default();
+            let grouping = self.settings.string("group-by").parse().unwrap_or_default();
+            self.feed_list.set_sorting(order, grouping);
+            self.mark_new();
+        }
+
+        fn setup_sorting(&self) {
+            let actions = SimpleActionGroup::new();
+            actions.add_action(&self.settings.create_action("sort-order"));
+            actions.add_action(&self.settings.create_action("group-by"));
+            actions.add_action(&self.settings.create_action("feed-layout"));
+            actions.add_action(&self.settings.create_action("hide-shorts"));
+            actions.add_action(&self.settings.create_action("hide-livestreams"));
+            self.obj().insert_action_group("view", Some(&actions));
+
+            self.apply_sorting();
+            let obj = self.obj();
+            for key in ["sort-order", "group-by"] {
+                self.settings.connect_changed(
+                    Some(key),
+                    clone!(@weak obj => move |_, _| {
+                        obj.imp().apply_sorting();
+                    }),
+                );
+            }
+        }
+
+        fn setup_last_visit(&self) {
+            let last_visit = self.settings.int64("last-visit");
+            if last_visit == 0 {
+                // First start, nothing can be new. The feed is not loaded yet, the seen videos
+                // are only known after the next visit.
+                self.set_last_visit();
+            } else {
+                self.last_visit.set(last_visit);
+                self.seen_urls.replace(crate::watched::seen());
+            }
+
+            let obj = self.obj();
+            self.btn_mark_seen
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    obj.mark_seen();
+                }));
+        }
+
+        /// Count a subscription as loaded in the current reload.
+        fn subscription_loaded(&self) {
+            if self.reloading.get() {
+                let loaded = self.loaded_subscriptions.get() + 1;
+                self.loaded_subscriptions
+                    .set(loaded.min(self.total_subscriptions.get()));
+                self.update_progress();
+            }
+        }
+
+        /// Generate the videos of a single failed subscription again.
+        fn retry(&self, subscription: &SubscriptionObject) {
+            self.error_label.remove_failure(subscription);
+            let sender = self
+                .reload_sender
+                .borrow()
+                .clone()
+                .expect("Reload sender should be set up");
+            let joiner = self
+                .joiner
+                .borrow()
+                .clone()
+                .expect("Joiner should be set up");
+            let error_store = self.error_store.borrow().clone();
+            let any_subscription = subscription.subscription();
+            let provider_subscription = subscription.provider_subscription();
+            tokio::spawn(async move {
+                let scheduler = Scheduler::from_env();
+                if let Some(s) = any_subscription {
+                    let client = reqwest::Client::new();
+                    let result = crate::generator::generate_scheduled(
+                        &joiner,
+                        &s,
+                        &error_store,
+                        &scheduler,
+                        &client,
+                    )
+                    .await;
+                    let _ = sender.send(match result {
+                        Ok(videos) => ReloadEvent::Generated(videos, vec![]),
+                        Err(failure) => ReloadEvent::Failed(failure),
+                    });
+                }
+                if let Some(s) = provider_subscription {
+                    let result = s
+                        .generate_scheduled(
+                            &error_store,
+                            &ProviderVideoFilters::new(&joiner.filters()),
+                            &scheduler,
+                        )
+                        .await;
+                    let _ = sender.send(match result {
+                        Ok(videos) => ReloadEvent::Generated(vec![], videos),
+                        Err(failure) => ReloadEvent::ProviderFailed(failure),
+                    });
+                }
+            });
+        }
+
+        fn remove_subscription(&self, subscription: &SubscriptionObject) {
+            let name = subscription
+                .property::<Option<String>>("name")
+                .unwrap_or_default();
+            let title = gettextrs::gettext("Removed {}").replace("{}", &name);
+            let window = self.obj().root().and_then(|r| r.downcast::<Window>().ok());
+
+            if let Some(s) = subscription.subscription() {
+                let subscription_list = self
+                    .joiner
+                    .borrow()
+                    .as_ref()
+                    .expect("Joiner should be set up")
+                    .subscription_list();
+                subscription_list.remove(s.clone());
+                if let Some(window) = &window {
+                    window.show_undo_toast(&title, move || subscription_list.add(s.clone()));
+                }
+            }
+            if let Some(s) = subscription.provider_subscription() {
+                let provider_subscription_list = self
+                    .provider_subscription_list
+                    .borrow()
+                    .clone()
+                    .expect("ProviderSubscriptionList should be set up");
+                provider_subscription_list.remove(s.clone());
+                if let Some(window) = &window {
+                    window
+                        .show_undo_toast(&title, move || provider_subscription_list.add(s.clone()));
+                }
+            }
+        }
+
+        /// Insert the videos generated for a single subscription which are not yet in the feed.
+        ///
+        /// While reloading, the count of new videos is only updated once the reload finished.
+        fn insert_generated(&self, videos: Vec<AnyVideo>, provider_videos: Vec<ProviderVideo>) {
+            let objects = {
+                let mut generated = self.generated_urls.borrow_mut();
+                let mut unknown = |url: String| {
+                    let unknown = !self.feed_list.contains(&url);
+                    generated.insert(url);
+                    unknown
+                };
+                let mut objects: Vec<VideoObject> = videos
+                    .into_iter()
+                    .filter(|v| unknown(v.url()))
+                    .map(VideoObject::new)
+                    .collect();
+                objects.extend(
+                    provider_videos
+                        .into_iter()
+                        .filter(|v| unknown(v.url.clone()))
+                        .map(VideoObject::from_provider),
+                );
+                objects
+            };
+            for object in &objects {
+                object.set_property("is-new", self.is_new(object));
+            }
+            self.feed_list.insert_items(objects);
+            if !self.reloading.get() {
+                self.mark_new();
+            }
+        }
+
+        /// Whether the subscription of the video failed to generate in the current reload.
+        fn subscription_failed(&self, video: &VideoObject) -> bool {
+            if let Some(video) = video.video() {
+                self.failed_subscriptions
+                    .borrow()
+                    .contains(&video.subscription())
+            } else if let Some(video) = video.provider_video() {
+                self.failed_provider_subscriptions
+                    .borrow()
+                    .contains(&video.subscription)
+            } else {
+                false
+            }
+        }
+
+        /// Remove the videos which were not generated again, e.g. of removed subscriptions.
+        ///
+        /// The videos of subscriptions which failed to generate are kept.
+        fn finish_reload(&self) {
+            let generated = self.generated_urls.take();
+            let items: Vec<VideoObject> = self
+                .feed_list
+                .items()
+                .into_iter()
+                .filter(|v| generated.contains(&v.url()) || self.subscription_failed(v))
+                .collect();
+            self.failed_subscriptions.take();
+            self.failed_provider_subscriptions.take();
+            let kept: HashSet<String> = items.iter().map(|v| v.url()).collect();
+            self.feed_list.update_items(items);
+            // The videos in the feed took over their metadata, forget the rest.
+            crate::metadata::retain(|url| kept.contains(url));
+            self.mark_new();
+        }
+
+        fn update_progress(&self) {
+            let loaded = self.loaded_subscriptions.get();
+            let total = self.total_subscriptions.get();
+            self.progress_reload.set_text(Some(
+                &gettextrs::ngettext(
+                    "{loaded}/{total} channel loaded",
+                    "{loaded}/{total} channels loaded",
+                    total as u32,
+                )
+                .replace("{loaded}", &loaded.to_string())
+                .replace("{total}", &total.to_string()),
+            ));
+            self.progress_reload.set_fraction(if total == 0 {
+                1.0
+            } else {
+                loaded as f64 / total as f64
+            });
+        }
+
+        /// Notify about the videos which were not in the feed before and are new since the last
+        /// visit.
+        fn notify_new(&self, previous: &HashSet<String>) {
+            let new_videos: Vec<VideoObject> = self
+                .feed_list
+                .items()
+                .into_iter()
+                .filter(|v| !previous.contains(&v.url()) && self.is_new(v))
+                .collect();
+            if !new_videos.is_empty() {
+                notification::notify_new_videos(&new_videos);
+            }
+        }
+
+        /// (Re)start the timer refreshing the feed in the background.
+        fn restart_background_refresh(&self) {
+            if let Some(source) = self.refresh_source.take() {
+                source.remove();
+            }
+            let interval = self.settings.uint("refresh-interval");
+            if interval == 0 {
+                return;
+            }
+            log::debug!("Refreshing the feed every {} minutes", interval);
+            let obj = self.obj();
+            let source = glib::timeout_add_seconds_local(
+                interval * 60,
+                clone!(@weak obj => @default-return Continue(false), move || {
+                    if !obj.property::<bool>("reloading") {
+                        log::debug!("Refreshing in the background");
+                        obj.imp().background_reload.set(true);
+                        obj.reload();
+                    }
+                    Continue(true)
+                }),
+            );
+            self.refresh_source.replace(Some(source));
+        }
+
+        fn setup_background_refresh(&self) {
+            self.restart_background_refresh();
+            let obj = self.obj();
+            self.settings.connect_changed(
+                Some("refresh-interval"),
+                clone!(@weak obj => move |_, _| {
+                    obj.imp().restart_background_refresh();
+                }),
+            );
+        }
+
         fn setup_reload(&self, obj: &super::FeedPage) {
             let joiner = self
                 .joiner
                 .borrow()
                 .clone()
                 .expect("Joiner should be set up");
+            let provider_subscription_list = self
+                .provider_subscription_list
+                .borrow()
+                .clone()
+                .expect("ProviderSubscriptionList should be set up");
 
             let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
-            let sender = sender.clone();
+            self.reload_sender.replace(Some(sender.clone()));
             let joiner = joiner.clone();
             let error_store = self.error_store.borrow().clone();
             let settings = self.settings.clone();
 
             self.btn_reload.connect_clicked(
-                clone!(@strong obj as s, @strong joiner, @strong error_store => move |_| {
+                clone!(@strong obj as s, @strong joiner, @strong provider_subscription_list, @strong error_store => move |_| {
+                    // A second reload at the same time would mix up the videos generated by both.
+                    if s.imp().reloading.get() {
+                        log::debug!("Already reloading, reload again afterwards");
+                        s.imp().reload_queued.set(true);
+                        return;
+                    }
                     log::debug!("Reloading");
                     s.set_property("reloading", &true);
+                    s.imp().previous_urls.replace(s.imp().feed_list.items().iter().map(|v| v.url()).collect());
+                    s.imp().generated_urls.take();
+                    s.imp().loaded_subscriptions.set(0);
+                    s.imp().total_subscriptions.set(
+                        joiner.subscription_list().iter().count() + provider_subscription_list.iter().count()
+                    );
+                    s.imp().update_progress();
 
                     let sender = sender.clone();
                     let joiner = joiner.clone();
+                    let provider_subscription_list = provider_subscription_list.clone();
                     let error_store = error_store.clone();
                     error_store.clear();
                     tokio::spawn(async move {
-                        let videos = joiner.generate(&error_store).await;
-                        let _ = sender.send(videos);
+                        let scheduler = Scheduler::from_env();
+                        futures::join!(
+                            crate::generator::generate_progressive(&joiner, &error_store, &scheduler, |result| {
+                                let _ = sender.send(match result {
+                                    Ok(videos) => ReloadEvent::Generated(videos, vec![]),
+                                    Err(failure) => ReloadEvent::Failed(failure),
+                                });
+                            }),
+                            provider_subscription_list.generate_progressive(&error_store, joiner.filters(), &scheduler, |result| {
+                                let _ = sender.send(match result {
+                                    Ok(videos) => ReloadEvent::Generated(vec![], videos),
+                                    Err(failure) => ReloadEvent::ProviderFailed(failure),
+                                });
+                            })
+                        );
+                        let _ = sender.send(ReloadEvent::Finished);
                     });
                 }),
             );
             receiver.attach(
                 None,
-                clone!(@strong obj as s, @strong settings => @default-return Continue(false), move |videos| {
-                    let yesterday = chrono::Local::now().date_naive() - chrono::Duration::days(1);
-                    let video_objects_iter = videos.into_iter().map(VideoObject::new);
-
-                    let video_objects = if settings.boolean("only-videos-yesterday") {
-                        video_objects_iter.filter(|v| v.uploaded().map(|d| d.date()) == Some(yesterday)).collect::<Vec<_>>()
-                    } else {
-                        video_objects_iter.collect::<Vec<_>>()
-                    };
-                    s.imp().feed_list.get().set_items(video_objects);
-                    s.set_property("reloading", &false);
+                clone!(@strong obj as s, @strong settings => @default-return Continue(false), move |event| {
+                    match event {
+                        ReloadEvent::Generated(videos, provider_videos) => {
+                            s.imp().subscription_loaded();
+                            s.imp().insert_generated(videos, provider_videos);
+                        }
+                        ReloadEvent::Failed(failure) => {
+                            s.imp().subscription_loaded();
+                            if s.imp().reloading.get() {
+                                s.imp().failed_subscriptions.borrow_mut().insert(failure.subscription.clone());
+                            }
+                            s.imp().error_label.add_failure(SubscriptionObject::new(failure.subscription), failure.errors);
+                        }
+                        ReloadEvent::ProviderFailed(failure) => {
+                            s.imp().subscription_loaded();
+                            if s.imp().reloading.get() {
+                                s.imp().failed_provider_subscriptions.borrow_mut().insert(failure.subscription.clone());
+                            }
+                            s.imp().error_label.add_failure(SubscriptionObject::from_provider(failure.subscription), failure.errors);
+                        }
+                        ReloadEvent::Finished => {
+                            s.imp().finish_reload();
+                            let previous = s.imp().previous_urls.take();
+                            if s.imp().background_reload.replace(false) && settings.boolean("notify-new-videos") {
+                                s.imp().notify_new(&previous);
+                            }
+                            s.set_property("reloading", &false);
+                            if s.imp().reload_queued.replace(false) {
+                                s.reload();
+                            }
+                        }
+                    }
                     Continue(true)
                 }),
             );
@@ -163,6 +661,24 @@ pub mod imp {
             // Setup Error Label
             self.error_label
                 .set_error_store(self.error_store.borrow().clone());
+            self.error_label.connect_local(
+                "retry",
+                false,
+                clone!(@weak obj => @default-return None, move |args| {
+                    let subscription = args[1].get::<SubscriptionObject>().expect("Parameter to be a SubscriptionObject");
+                    obj.imp().retry(&subscription);
+                    None
+                }),
+            );
+            self.error_label.connect_local(
+                "remove",
+                false,
+                clone!(@weak obj => @default-return None, move |args| {
+                    let subscription = args[1].get::<SubscriptionObject>().expect("Parameter to be a SubscriptionObject");
+                    obj.imp().remove_subscription(&subscription);
+                    None
+                }),
+            );
 
             // Simulate reload on startup.
             self.btn_reload.emit_clicked();
@@ -184,7 +700,11 @@ pub mod imp {
                     .clone()
                     .expect("PlaylistManager has to be set up"),
             );
+            self.setup_last_visit();
+            self.setup_sorting();
+            self.feed_list.bind_filter_settings();
             self.setup_reload(obj);
+            self.setup_background_refresh();
             self.setup_add_subscription(obj);
         }
     }
@@ -196,6 +716,7 @@ pub mod imp {
         type ParentType = gtk::Box;
 
         fn class_init(klass: &mut Self::Class) {
+            DateRangeButton::ensure_type();
             Self::bind_template(klass);
             Utility::bind_template_callbacks(klass);
         }
@@ -18,9 +18,16 @@
  *
  */
 
+pub(crate) mod comments_window;
+pub(crate) mod date_range;
+mod date_range_button;
 mod error_label;
 pub(crate) mod feed_item;
 pub(crate) mod feed_item_object;
 pub(crate) mod feed_list;
 pub(crate) mod feed_page;
+pub(crate) mod player_page;
+pub(crate) mod queue_window;
+pub(crate) mod sorting;
 mod thumbnail;
+pub(crate) mod video_page;
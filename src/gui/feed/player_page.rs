@@ -0,0 +1,319 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+use gdk::subclass::prelude::ObjectSubclassIsExt;
+use gtk::glib::Object;
+
+use super::feed_item_object::VideoObject;
+
+gtk::glib::wrapper! {
+    pub struct PlayerPage(ObjectSubclass<imp::PlayerPage>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl PlayerPage {
+    pub fn new() -> Self {
+        Object::builder::<Self>().build()
+    }
+
+    /// Play the video from the uri of its media, resuming where it was left off the last time.
+    pub fn play(&self, video: &VideoObject, uri: &str) {
+        self.imp().play(video, uri);
+    }
+
+    /// Stop playing, remembering where the video was left off.
+    pub fn stop(&self) {
+        self.imp().stop();
+    }
+}
+
+impl Default for PlayerPage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use gdk::gio;
+    use gdk::glib::clone;
+    use gdk::glib::ParamSpecObject;
+    use gdk::glib::SignalHandlerId;
+    use gdk::glib::Value;
+    use glib::subclass::InitializingObject;
+    use glib::ParamSpec;
+    use gtk::glib;
+    use gtk::prelude::*;
+    use gtk::subclass::prelude::*;
+    use gtk::CallbackAction;
+    use gtk::CompositeTemplate;
+    use gtk::Shortcut;
+    use gtk::ShortcutController;
+    use gtk::ShortcutTrigger;
+    use once_cell::sync::Lazy;
+
+    use crate::gui::feed::feed_item_object::VideoObject;
+    use crate::gui::window::Window;
+    use crate::watched::ResumePosition;
+
+    /// The progress is reported at most this often while playing, in microseconds.
+    const PROGRESS_INTERVAL: i64 = 1_000_000;
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/ui/player_page.ui")]
+    pub struct PlayerPage {
+        #[template_child]
+        header_bar: TemplateChild<libadwaita::HeaderBar>,
+        #[template_child]
+        video_view: TemplateChild<gtk::Video>,
+
+        video: RefCell<Option<VideoObject>>,
+        stream: RefCell<Option<gtk::MediaFile>>,
+        /// The timestamp of the stream the progress was last reported at.
+        reported_timestamp: Cell<i64>,
+        /// Shows the header bar again once the window left fullscreen.
+        fullscreen_handler: RefCell<Option<(Window, SignalHandlerId)>>,
+    }
+
+    impl PlayerPage {
+        fn window(&self) -> Option<Window> {
+            self.obj().root().and_then(|r| r.downcast::<Window>().ok())
+        }
+
+        fn resume_position(stream: &gtk::MediaFile) -> ResumePosition {
+            ResumePosition {
+                position: stream.timestamp() as f64 / 1_000_000.0,
+                duration: Some(stream.duration())
+                    .filter(|d| d > &0)
+                    .map(|d| d as f64 / 1_000_000.0),
+            }
+        }
+
+        pub(super) fn play(&self, video: &VideoObject, uri: &str) {
+            self.stop();
+            let obj = self.obj();
+            let start = video.start_builtin();
+            let stream = gtk::MediaFile::for_file(&gio::File::for_uri(uri));
+
+            if let Some(start) = start {
+                stream.connect_prepared_notify(move |stream| {
+                    if stream.is_prepared() && stream.is_seekable() {
+                        stream.seek((start * 1_000_000.0) as i64);
+                    }
+                });
+            }
+            let update = clone!(@weak obj, @weak video => move |stream: &gtk::MediaFile| {
+                obj.imp().reported_timestamp.set(stream.timestamp());
+                video.update_builtin(!stream.is_playing(), Self::resume_position(stream));
+            });
+            self.reported_timestamp.set(0);
+            stream.connect_timestamp_notify(clone!(@weak obj, @strong update => move |stream| {
+                let reported = obj.imp().reported_timestamp.get();
+                if (stream.timestamp() - reported).abs() >= PROGRESS_INTERVAL {
+                    update(stream);
+                }
+            }));
+            stream.connect_playing_notify(update);
+            stream.connect_ended_notify(clone!(@weak obj => move |stream| {
+                if stream.is_ended() {
+                    match obj.imp().window() {
+                        Some(window) => window.hide_player(),
+                        None => obj.imp().stop(),
+                    }
+                }
+            }));
+            stream.connect_error_notify(clone!(@weak obj => move |stream| {
+                if let Some(error) = stream.error() {
+                    log::error!("Failed to play the video in the application: {}", error);
+                    if let Some(window) = obj.imp().window() {
+                        window.show_toast(&gettextrs::gettext("Failed to play the video: {}").replace("{}", error.message()));
+                    }
+                }
+            }));
+
+            self.video_view.set_media_stream(Some(&stream));
+            stream.play();
+            self.stream.replace(Some(stream));
+            obj.set_property("video", video);
+        }
+
+        pub(super) fn stop(&self) {
+            let stream = match self.stream.take() {
+                Some(stream) => stream,
+                None => return,
+            };
+            let resume = Self::resume_position(&stream);
+            let finished = stream.is_ended();
+            stream.pause();
+            self.video_view.set_media_stream(None::<&gtk::MediaStream>);
+            if let Some(video) = self.video.borrow().as_ref() {
+                video.stop_builtin(finished, resume);
+            }
+        }
+
+        fn toggle_playing(&self) {
+            if let Some(stream) = self.stream.borrow().as_ref() {
+                stream.set_playing(!stream.is_playing());
+            }
+        }
+
+        fn set_fullscreen(&self, fullscreen: bool) {
+            if let Some(window) = self.window() {
+                window.set_fullscreened(fullscreen);
+            }
+        }
+
+        fn toggle_fullscreen(&self) {
+            if let Some(window) = self.window() {
+                self.set_fullscreen(!window.is_fullscreened());
+            }
+        }
+
+        fn setup_shortcuts(&self) {
+            let obj = self.obj();
+            let controller = ShortcutController::new();
+            for (accelerator, action) in [
+                ("f", "fullscreen"),
+                ("Escape", "leave-fullscreen"),
+                ("space", "play-pause"),
+                ("k", "play-pause"),
+            ] {
+                let callback =
+                    CallbackAction::new(clone!(@weak obj => @default-return false, move |_, _| {
+                        match action {
+                            "fullscreen" => obj.imp().toggle_fullscreen(),
+                            // Let e.g. the window handle the key if not in fullscreen.
+                            "leave-fullscreen" => match obj.imp().window() {
+                                Some(window) if window.is_fullscreened() => {
+                                    window.set_fullscreened(false)
+                                }
+                                _ => return false,
+                            },
+                            _ => obj.imp().toggle_playing(),
+                        }
+                        true
+                    }));
+                controller.add_shortcut(Shortcut::new(
+                    ShortcutTrigger::parse_string(accelerator),
+                    Some(callback),
+                ));
+            }
+            obj.add_controller(controller);
+
+            let double_click = gtk::GestureClick::new();
+            double_click.connect_pressed(clone!(@weak obj => move |_, presses, _, _| {
+                if presses == 2 {
+                    obj.imp().toggle_fullscreen();
+                }
+            }));
+            self.video_view.add_controller(double_click);
+        }
+    }
+
+    #[gtk::template_callbacks]
+    impl PlayerPage {
+        #[template_callback]
+        fn handle_back(&self) {
+            if let Some(window) = self.window() {
+                window.hide_player();
+            }
+        }
+
+        #[template_callback]
+        fn handle_fullscreen(&self) {
+            self.toggle_fullscreen();
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PlayerPage {
+        const NAME: &'static str = "TFPlayerPage";
+        type Type = super::PlayerPage;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PlayerPage {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.setup_shortcuts();
+        }
+
+        fn properties() -> &'static [ParamSpec] {
+            static PROPERTIES: Lazy<Vec<ParamSpec>> =
+                Lazy::new(|| vec![ParamSpecObject::builder::<VideoObject>("video").build()]);
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &Value, pspec: &ParamSpec) {
+            match pspec.name() {
+                "video" => {
+                    let value: Option<VideoObject> =
+                        value.get().expect("Property video of incorrect type");
+                    self.video.replace(value);
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+            match pspec.name() {
+                "video" => self.video.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl WidgetImpl for PlayerPage {
+        fn map(&self) {
+            self.parent_map();
+            if let Some(window) = self.window() {
+                let header_bar = self.header_bar.get();
+                let handler = window.connect_fullscreened_notify(move |window| {
+                    header_bar.set_visible(!window.is_fullscreened());
+                });
+                self.fullscreen_handler.replace(Some((window, handler)));
+            }
+        }
+
+        fn unmap(&self) {
+            // Leaving the page, e.g. by swiping back, stops playing.
+            self.stop();
+            self.set_fullscreen(false);
+            if let Some((window, handler)) = self.fullscreen_handler.take() {
+                window.disconnect(handler);
+            }
+            self.header_bar.set_visible(true);
+            self.parent_unmap();
+        }
+    }
+
+    impl BoxImpl for PlayerPage {}
+}
@@ -0,0 +1,196 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+use gdk::gio::ListStore;
+use gdk::glib::clone;
+use gdk::subclass::prelude::ObjectSubclassIsExt;
+use gtk::glib::{self, Object};
+use gtk::prelude::*;
+use libadwaita::prelude::ActionRowExt;
+use libadwaita::prelude::PreferencesRowExt;
+
+use super::feed_item_object::VideoObject;
+
+gtk::glib::wrapper! {
+    pub struct QueueWindow(ObjectSubclass<imp::QueueWindow>)
+        @extends libadwaita::Window, gtk::Window, gtk::Widget,
+        @implements gtk::gio::ActionGroup, gtk::gio::ActionMap, gtk::Accessible, gtk::Buildable,
+            gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+impl QueueWindow {
+    /// Create a window to reorder and play the given queue of [VideoObject]s.
+    pub fn new(queue: &ListStore) -> Self {
+        let s: Self = Object::builder().build();
+        s.set_queue(queue);
+        s
+    }
+
+    fn set_queue(&self, queue: &ListStore) {
+        let imp = self.imp();
+        imp.list_queue.bind_model(
+            Some(queue),
+            clone!(@weak queue => @default-panic, move |item| {
+                let video = item
+                    .downcast_ref::<VideoObject>()
+                    .expect("The item has to be an `VideoObject`.");
+                queue_row(&queue, video).upcast()
+            }),
+        );
+
+        let update_empty = clone!(@weak self as s => move |queue: &ListStore| {
+            let empty = queue.n_items() == 0;
+            s.imp().status_empty.set_visible(empty);
+            s.imp().scrolled_queue.set_visible(!empty);
+            s.imp().btn_play.set_sensitive(!empty);
+            s.imp().btn_clear.set_sensitive(!empty);
+        });
+        update_empty(queue);
+        queue.connect_items_changed(move |queue, _, _, _| update_empty(queue));
+
+        imp.btn_clear
+            .connect_clicked(clone!(@weak queue => move |_| queue.remove_all()));
+        imp.btn_play
+            .connect_clicked(clone!(@weak queue => move |_| play(&queue)));
+    }
+}
+
+/// The position of the video in the queue.
+fn position(queue: &ListStore, video: &VideoObject) -> Option<u32> {
+    (0..queue.n_items()).find(|i| queue.item(*i).as_ref() == Some(video.upcast_ref()))
+}
+
+/// Move the video by `offset` places in the queue.
+fn move_by(queue: &ListStore, video: &VideoObject, offset: i32) {
+    if let Some(current) = position(queue, video) {
+        let target = current as i32 + offset;
+        if target >= 0 && target < queue.n_items() as i32 {
+            queue.remove(current);
+            queue.insert(target as u32, video);
+        }
+    }
+}
+
+fn queue_row(queue: &ListStore, video: &VideoObject) -> libadwaita::ActionRow {
+    let row = libadwaita::ActionRow::builder()
+        .title_lines(2)
+        .subtitle_lines(1)
+        .build();
+    row.set_title(&glib::markup_escape_text(
+        &video
+            .property::<Option<String>>("title")
+            .unwrap_or_default(),
+    ));
+    row.set_subtitle(&glib::markup_escape_text(
+        &video
+            .property::<Option<String>>("author")
+            .unwrap_or_default(),
+    ));
+
+    for (icon, tooltip, offset) in [
+        ("go-up-symbolic", gettextrs::gettext("Move Up"), -1),
+        ("go-down-symbolic", gettextrs::gettext("Move Down"), 1),
+    ] {
+        let button = gtk::Button::builder()
+            .icon_name(icon)
+            .tooltip_text(tooltip)
+            .valign(gtk::Align::Center)
+            .build();
+        button.add_css_class("flat");
+        button.connect_clicked(clone!(@weak queue, @weak video => move |_| {
+            move_by(&queue, &video, offset);
+        }));
+        row.add_suffix(&button);
+    }
+
+    let button_remove = gtk::Button::builder()
+        .icon_name("user-trash-symbolic")
+        .tooltip_text(gettextrs::gettext("Remove from Queue"))
+        .valign(gtk::Align::Center)
+        .build();
+    button_remove.add_css_class("flat");
+    button_remove.connect_clicked(clone!(@weak queue, @weak video => move |_| {
+        if let Some(position) = position(&queue, &video) {
+            queue.remove(position);
+        }
+    }));
+    row.add_suffix(&button_remove);
+
+    row
+}
+
+/// Play all videos of the queue in one player, removing every video that was played until the
+/// end.
+pub fn play(queue: &ListStore) {
+    let videos: Vec<VideoObject> = (0..queue.n_items())
+        .filter_map(|i| queue.item(i).and_then(|v| v.downcast::<VideoObject>().ok()))
+        .collect();
+    VideoObject::play_all(
+        videos,
+        clone!(@weak queue => move |video| {
+            if let Some(position) = position(&queue, video) {
+                queue.remove(position);
+            }
+        }),
+    );
+}
+
+pub mod imp {
+    use glib::subclass::InitializingObject;
+    use gtk::glib;
+    use gtk::subclass::prelude::*;
+    use gtk::CompositeTemplate;
+    use libadwaita::subclass::prelude::AdwWindowImpl;
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/ui/queue_window.ui")]
+    pub struct QueueWindow {
+        #[template_child]
+        pub(super) list_queue: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub(super) scrolled_queue: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub(super) status_empty: TemplateChild<libadwaita::StatusPage>,
+        #[template_child]
+        pub(super) btn_play: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub(super) btn_clear: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for QueueWindow {
+        const NAME: &'static str = "TFQueueWindow";
+        type Type = super::QueueWindow;
+        type ParentType = libadwaita::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for QueueWindow {}
+    impl WidgetImpl for QueueWindow {}
+    impl WindowImpl for QueueWindow {}
+    impl AdwWindowImpl for QueueWindow {}
+}
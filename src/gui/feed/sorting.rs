@@ -0,0 +1,260 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Sorting and grouping of the videos of a [FeedList][super::feed_list::FeedList].
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use chrono::{NaiveDate, NaiveDateTime, TimeZone};
+use gdk::prelude::ObjectExt;
+
+use super::feed_item_object::VideoObject;
+
+/// The order of the videos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    Newest,
+    Oldest,
+    Channel,
+    Title,
+    /// Shortest first.
+    Duration,
+}
+
+impl FromStr for SortOrder {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "newest" => Ok(SortOrder::Newest),
+            "oldest" => Ok(SortOrder::Oldest),
+            "channel" => Ok(SortOrder::Channel),
+            "title" => Ok(SortOrder::Title),
+            "duration" => Ok(SortOrder::Duration),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How to group the videos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Grouping {
+    #[default]
+    None,
+    Day,
+    Channel,
+}
+
+impl FromStr for Grouping {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Grouping::None),
+            "day" => Ok(Grouping::Day),
+            "channel" => Ok(Grouping::Channel),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The day a video was uploaded, relative to today.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Day {
+    Today,
+    Yesterday,
+    ThisWeek,
+    Earlier,
+}
+
+impl Day {
+    fn of(uploaded: Option<NaiveDateTime>, today: NaiveDate) -> Self {
+        let uploaded = match uploaded {
+            Some(uploaded) => chrono::Local.from_utc_datetime(&uploaded).date_naive(),
+            None => return Day::Earlier,
+        };
+        let days = (today - uploaded).num_days();
+        match days {
+            d if d <= 0 => Day::Today,
+            1 => Day::Yesterday,
+            d if d < 7 => Day::ThisWeek,
+            _ => Day::Earlier,
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            Day::Today => gettextrs::gettext("Today"),
+            Day::Yesterday => gettextrs::gettext("Yesterday"),
+            Day::ThisWeek => gettextrs::gettext("This Week"),
+            Day::Earlier => gettextrs::gettext("Earlier"),
+        }
+    }
+}
+
+fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// The values a video is sorted by, read once per video instead of on every comparison.
+struct SortValues {
+    uploaded: Option<NaiveDateTime>,
+    day: Day,
+    author: String,
+    title: String,
+    duration: Option<i64>,
+}
+
+impl SortValues {
+    fn of(video: &VideoObject, today: NaiveDate) -> Self {
+        let uploaded = video.uploaded();
+        Self {
+            uploaded,
+            day: Day::of(uploaded, today),
+            author: video
+                .property::<Option<String>>("author")
+                .unwrap_or_default()
+                .to_lowercase(),
+            title: video
+                .property::<Option<String>>("title")
+                .unwrap_or_default()
+                .to_lowercase(),
+            duration: video.duration(),
+        }
+    }
+}
+
+fn compare(a: &SortValues, b: &SortValues, order: SortOrder) -> Ordering {
+    match order {
+        SortOrder::Newest => b.uploaded.cmp(&a.uploaded),
+        SortOrder::Oldest => a.uploaded.cmp(&b.uploaded),
+        SortOrder::Channel => a
+            .author
+            .cmp(&b.author)
+            .then_with(|| b.uploaded.cmp(&a.uploaded)),
+        SortOrder::Title => a.title.cmp(&b.title),
+        // Videos with unknown duration last.
+        SortOrder::Duration => match (a.duration, b.duration) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        },
+    }
+}
+
+fn compare_groups(
+    a: &SortValues,
+    b: &SortValues,
+    grouping: Grouping,
+    order: SortOrder,
+) -> Ordering {
+    match grouping {
+        Grouping::None => Ordering::Equal,
+        Grouping::Day if order == SortOrder::Oldest => b.day.cmp(&a.day),
+        Grouping::Day => a.day.cmp(&b.day),
+        Grouping::Channel => a.author.cmp(&b.author),
+    }
+}
+
+/// Sort the videos by group first, by the order inside of a group.
+pub fn sort(videos: &mut [VideoObject], order: SortOrder, grouping: Grouping) {
+    let today = today();
+    let mut sorted: Vec<(SortValues, VideoObject)> = videos
+        .iter()
+        .map(|v| (SortValues::of(v, today), v.clone()))
+        .collect();
+    sorted.sort_by(|(a, _), (b, _)| {
+        compare_groups(a, b, grouping, order).then_with(|| compare(a, b, order))
+    });
+    for (video, (_, sorted)) in videos.iter_mut().zip(sorted) {
+        *video = sorted;
+    }
+}
+
+/// Insert the new videos into the videos, both have to be sorted. Returns the positions the new
+/// videos were inserted at, in ascending order.
+pub fn insert_sorted(
+    videos: &mut Vec<VideoObject>,
+    new: Vec<VideoObject>,
+    order: SortOrder,
+    grouping: Grouping,
+) -> Vec<usize> {
+    let today = today();
+    let mut positions = Vec::with_capacity(new.len());
+    let mut start = 0;
+    for video in new {
+        let values = SortValues::of(&video, today);
+        // Behind the videos sorted equally, like a stable sort would.
+        let position = start
+            + videos[start..].partition_point(|v| {
+                let v = SortValues::of(v, today);
+                compare_groups(&v, &values, grouping, order)
+                    .then_with(|| compare(&v, &values, order))
+                    .is_le()
+            });
+        videos.insert(position, video);
+        positions.push(position);
+        start = position + 1;
+    }
+    positions
+}
+
+/// The title of the group of the video.
+fn group_title(video: &VideoObject, grouping: Grouping, today: NaiveDate) -> Option<String> {
+    match grouping {
+        Grouping::None => None,
+        Grouping::Day => Some(Day::of(video.uploaded(), today).title()),
+        Grouping::Channel => video.property::<Option<String>>("author"),
+    }
+}
+
+/// Set the `group-header` of the videos at the positions and of the videos following them, e.g.
+/// after they were inserted. The videos have to be sorted.
+pub fn update_group_headers(videos: &[VideoObject], positions: &[usize], grouping: Grouping) {
+    let today = today();
+    let group = |i: usize| group_title(&videos[i], grouping, today);
+    for i in positions.iter().flat_map(|&p| [p, p + 1]) {
+        if i >= videos.len() {
+            continue;
+        }
+        let current = group(i);
+        let previous = i.checked_sub(1).and_then(group);
+        let header = if current != previous { current } else { None };
+        videos[i].set_property("group-header", header);
+    }
+}
+
+/// Set the `group-header` of the first video of each group, the videos have to be sorted.
+pub fn set_group_headers(videos: &[VideoObject], grouping: Grouping) {
+    let today = today();
+    let mut previous: Option<String> = None;
+    for video in videos {
+        let group = group_title(video, grouping, today);
+        let header = if group != previous {
+            group.clone()
+        } else {
+            None
+        };
+        video.set_property("group-header", header);
+        previous = group;
+    }
+}
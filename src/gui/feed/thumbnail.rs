@@ -26,23 +26,25 @@ use gdk::{
 
 async fn download(thumbnail_url: String) -> Option<image::DynamicImage> {
     log::debug!("Getting thumbnail from url {}", thumbnail_url);
-    let response = reqwest::get(&thumbnail_url.clone()).await;
-
-    if response.is_err() {
-        log::error!("Failed getting thumbnail for url {}, abort", thumbnail_url);
-        return None;
+    // Thumbnails are not kept in the HTTP cache, they are stored until closing the application.
+    let response = async {
+        reqwest::get(&thumbnail_url)
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await
     }
-
-    let parsed = response.unwrap().bytes().await;
-
-    if parsed.is_err() {
-        log::error!("Failed getting thumbnail for url {}, abort", thumbnail_url);
-        return None;
+    .await;
+
+    if let Err(e) = &response {
+        log::error!(
+            "Failed getting thumbnail for url {}, abort: {}",
+            thumbnail_url,
+            e
+        );
     }
 
-    let parsed_bytes = parsed.unwrap();
-
-    image::load_from_memory(&parsed_bytes).ok()
+    image::load_from_memory(&response.ok()?).ok()
 }
 
 gtk::glib::wrapper! {
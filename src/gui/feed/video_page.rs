@@ -0,0 +1,422 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+use gdk::subclass::prelude::ObjectSubclassIsExt;
+use gtk::glib::{self, Object};
+use gtk::prelude::*;
+use tf_join::AnyVideo;
+use tf_playlist::PlaylistManager;
+
+use super::feed_item_object::VideoObject;
+
+gtk::glib::wrapper! {
+    pub struct VideoPage(ObjectSubclass<imp::VideoPage>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::gio::ActionGroup, gtk::gio::ActionMap, gtk::Accessible, gtk::Buildable,
+            gtk::ConstraintTarget;
+}
+
+impl VideoPage {
+    pub fn new() -> Self {
+        Object::builder::<Self>().build()
+    }
+
+    pub fn set_playlist_manager(&self, playlist_manager: PlaylistManager<String, AnyVideo>) {
+        self.imp().playlist_manager.replace(Some(playlist_manager));
+    }
+
+    /// Show the given video and load its details.
+    pub fn set_video(&self, video: &VideoObject) {
+        self.set_property("video", video);
+        self.imp().load_details();
+    }
+}
+
+impl Default for VideoPage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert the description into Pango markup, with clickable links and timestamps.
+///
+/// Timestamps link to `t:<seconds>`.
+fn description_markup(description: &str) -> String {
+    static LINK: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+        regex::Regex::new(r"https?://[^\s<>]+[^\s<>.,;:!?)\]'\x22]").expect("Regex to be valid")
+    });
+    let links = LINK.find_iter(description).map(|m| {
+        let url = m.as_str();
+        (m.start(), m.end(), url.to_owned())
+    });
+    let timestamps = crate::details::TIMESTAMP
+        .find_iter(description)
+        .filter_map(|m| {
+            let seconds = crate::details::parse_timestamp(m.as_str())?;
+            Some((m.start(), m.end(), format!("t:{}", seconds)))
+        });
+
+    let mut markers: Vec<(usize, usize, String)> = links.collect();
+    // Timestamps inside of links are part of the link.
+    markers.extend(
+        timestamps
+            .filter(|(start, _, _)| !markers.iter().any(|(s, e, _)| s <= start && start < e))
+            .collect::<Vec<_>>(),
+    );
+    markers.sort_by_key(|(start, _, _)| *start);
+
+    let mut markup = String::new();
+    let mut position = 0;
+    for (start, end, target) in markers {
+        markup.push_str(&glib::markup_escape_text(&description[position..start]));
+        markup.push_str(&format!(
+            "<a href=\"{}\">{}</a>",
+            glib::markup_escape_text(&target),
+            glib::markup_escape_text(&description[start..end])
+        ));
+        position = end;
+    }
+    markup.push_str(&glib::markup_escape_text(&description[position..]));
+    markup
+}
+
+pub mod imp {
+    use std::cell::RefCell;
+
+    use gdk::glib::clone;
+    use gdk::glib::MainContext;
+    use gdk::glib::ParamSpecObject;
+    use gdk::glib::Value;
+    use gdk::glib::PRIORITY_DEFAULT;
+    use glib::subclass::InitializingObject;
+    use glib::ParamSpec;
+    use gtk::glib;
+    use gtk::prelude::*;
+    use gtk::subclass::prelude::*;
+    use gtk::CompositeTemplate;
+    use gtk::Inhibit;
+    use libadwaita::prelude::ActionRowExt;
+    use once_cell::sync::Lazy;
+    use tf_core::Video;
+    use tf_join::AnyVideo;
+    use tf_playlist::PlaylistManager;
+
+    use crate::details::VideoDetails;
+    use crate::gui::feed::feed_item_object::{format_duration, format_views, VideoObject};
+    use crate::gui::feed::thumbnail::Thumbnail;
+    use crate::gui::window::Window;
+
+    #[derive(CompositeTemplate, Default)]
+    #[template(resource = "/ui/video_page.ui")]
+    pub struct VideoPage {
+        #[template_child]
+        label_info: TemplateChild<gtk::Label>,
+        #[template_child]
+        label_description: TemplateChild<gtk::Label>,
+        #[template_child]
+        spinner: TemplateChild<gtk::Spinner>,
+        #[template_child]
+        btn_watch_later: TemplateChild<gtk::Button>,
+        #[template_child]
+        btn_comments: TemplateChild<gtk::Button>,
+        #[template_child]
+        box_chapters: TemplateChild<gtk::Box>,
+        #[template_child]
+        list_chapters: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        box_tags: TemplateChild<gtk::Box>,
+        #[template_child]
+        label_tags: TemplateChild<gtk::Label>,
+
+        video: RefCell<Option<VideoObject>>,
+        pub(super) playlist_manager: RefCell<Option<PlaylistManager<String, AnyVideo>>>,
+    }
+
+    impl VideoPage {
+        fn window(&self) -> Option<Window> {
+            self.obj().root().and_then(|r| r.downcast::<Window>().ok())
+        }
+
+        fn url(&self) -> Option<String> {
+            self.video
+                .borrow()
+                .as_ref()
+                .and_then(|v| v.property::<Option<String>>("url"))
+        }
+
+        pub(super) fn load_details(&self) {
+            self.set_details(None);
+            self.label_description.set_visible(false);
+            self.spinner.set_visible(true);
+            self.btn_watch_later.set_sensitive(
+                self.video
+                    .borrow()
+                    .as_ref()
+                    .and_then(|v| v.video())
+                    .is_some(),
+            );
+
+            self.btn_comments.set_sensitive(
+                self.video
+                    .borrow()
+                    .as_ref()
+                    .and_then(|v| v.video())
+                    .map(|v| crate::comments::supported(&v.platform()))
+                    .unwrap_or_default(),
+            );
+
+            let url = self.url();
+            let platform = self
+                .video
+                .borrow()
+                .as_ref()
+                .and_then(|v| v.video())
+                .map(|v| v.platform());
+            let (url, platform) = match (url, platform) {
+                (Some(url), Some(platform)) => (url, platform),
+                _ => {
+                    // Videos of providers do not have details.
+                    self.spinner.set_visible(false);
+                    return;
+                }
+            };
+
+            let obj = self.obj();
+            let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+            tokio::spawn(clone!(@strong url => async move {
+                let _ = sender.send(crate::details::video_details(url, platform).await);
+            }));
+            receiver.attach(
+                None,
+                clone!(@weak obj => @default-return Continue(false), move |details| {
+                    // Another video may be shown in the meantime.
+                    if obj.imp().url().as_ref() == Some(&url) {
+                        obj.imp().set_details(details);
+                    }
+                    Continue(false)
+                }),
+            );
+        }
+
+        fn set_details(&self, details: Option<VideoDetails>) {
+            self.spinner.set_visible(false);
+            let video = self.video.borrow().clone();
+
+            let date = details
+                .as_ref()
+                .and_then(|d| d.uploaded)
+                .or_else(|| video.as_ref().and_then(|v| v.uploaded()))
+                .map(|d| d.format(&gettextrs::gettext("%F %T")).to_string());
+            let views = details
+                .as_ref()
+                .and_then(|d| d.views)
+                .or_else(|| {
+                    video
+                        .as_ref()
+                        .and_then(|v| v.metadata())
+                        .and_then(|m| m.views)
+                })
+                .map(format_views);
+            let likes = details.as_ref().and_then(|d| d.likes).map(|l| {
+                gettextrs::ngettext("{} like", "{} likes", l as u32).replace("{}", &l.to_string())
+            });
+            let info: Vec<String> = [date, views, likes].into_iter().flatten().collect();
+            self.label_info.set_label(&info.join(" · "));
+            self.label_info.set_visible(!info.is_empty());
+
+            let description = details
+                .as_ref()
+                .map(|d| d.description.trim())
+                .filter(|d| !d.is_empty());
+            self.label_description.set_visible(details.is_some());
+            match description {
+                Some(description) => self
+                    .label_description
+                    .set_markup(&super::description_markup(description)),
+                None => self
+                    .label_description
+                    .set_label(&gettextrs::gettext("No description")),
+            }
+
+            while let Some(row) = self.list_chapters.first_child() {
+                self.list_chapters.remove(&row);
+            }
+            let chapters = details
+                .as_ref()
+                .map(|d| d.chapters.clone())
+                .unwrap_or_default();
+            self.box_chapters.set_visible(!chapters.is_empty());
+            for chapter in chapters {
+                let row = libadwaita::ActionRow::builder()
+                    .title(glib::markup_escape_text(&chapter.title).as_str())
+                    .subtitle(format_duration(chapter.start))
+                    .activatable(true)
+                    .build();
+                row.connect_activated(clone!(@strong self.video as video => move |_| {
+                    if let Some(video) = video.borrow().as_ref() {
+                        video.play_at(chapter.start);
+                    }
+                }));
+                self.list_chapters.append(&row);
+            }
+
+            let tags = details.map(|d| d.tags).unwrap_or_default();
+            self.box_tags.set_visible(!tags.is_empty());
+            self.label_tags.set_label(
+                &tags
+                    .iter()
+                    .map(|t| format!("#{}", t))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+        }
+    }
+
+    #[gtk::template_callbacks]
+    impl VideoPage {
+        #[template_callback]
+        fn handle_back(&self) {
+            if let Some(window) = self.window() {
+                window.hide_video();
+            }
+        }
+
+        #[template_callback]
+        fn handle_play(&self) {
+            if let Some(video) = self.video.borrow().as_ref() {
+                match self.window() {
+                    Some(window) => window.play_video(video),
+                    None => video.play(),
+                }
+            }
+        }
+
+        #[template_callback]
+        fn handle_download(&self) {
+            if let Some(video) = self.video.borrow().as_ref() {
+                video.download();
+            }
+        }
+
+        #[template_callback]
+        fn handle_comments(&self) {
+            if let (Some(video), Some(window)) = (self.video.borrow().as_ref(), self.window()) {
+                window.show_comments(video);
+            }
+        }
+
+        #[template_callback]
+        fn handle_watch_later(&self) {
+            let video = self.video.borrow().as_ref().and_then(|v| v.video());
+            let playlist_manager = self.playlist_manager.borrow().clone();
+            let (video, mut playlist_manager) = match (video, playlist_manager) {
+                (Some(video), Some(playlist_manager)) => (video, playlist_manager),
+                _ => return,
+            };
+            let removed = playlist_manager
+                .items(&"WATCHLATER".to_owned())
+                .contains(&video);
+            playlist_manager.toggle(&"WATCHLATER".to_owned(), &video);
+
+            if let Some(window) = self.window() {
+                let title = if removed {
+                    gettextrs::gettext("Removed {} from Watch Later")
+                } else {
+                    gettextrs::gettext("Added {} to Watch Later")
+                }
+                .replace("{}", &video.title());
+                window.show_undo_toast(&title, move || {
+                    let mut playlist_manager = playlist_manager.clone();
+                    if playlist_manager
+                        .items(&"WATCHLATER".to_owned())
+                        .contains(&video)
+                        != removed
+                    {
+                        playlist_manager.toggle(&"WATCHLATER".to_owned(), &video);
+                    }
+                });
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for VideoPage {
+        const NAME: &'static str = "TFVideoPage";
+        type Type = super::VideoPage;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            Thumbnail::ensure_type();
+            Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for VideoPage {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            self.label_description.connect_activate_link(
+                clone!(@weak obj => @default-return Inhibit(false), move |_, uri| {
+                    let seconds = uri.strip_prefix("t:").and_then(|s| s.parse().ok());
+                    let video = obj.imp().video.borrow().clone();
+                    match (seconds, video) {
+                        (Some(seconds), Some(video)) => {
+                            video.play_at(seconds);
+                            Inhibit(true)
+                        }
+                        _ => Inhibit(false),
+                    }
+                }),
+            );
+        }
+
+        fn properties() -> &'static [ParamSpec] {
+            static PROPERTIES: Lazy<Vec<ParamSpec>> =
+                Lazy::new(|| vec![ParamSpecObject::builder::<VideoObject>("video").build()]);
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &Value, pspec: &ParamSpec) {
+            match pspec.name() {
+                "video" => {
+                    let value: Option<VideoObject> =
+                        value.get().expect("Property video of incorrect type");
+                    self.video.replace(value);
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+            match pspec.name() {
+                "video" => self.video.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl WidgetImpl for VideoPage {}
+    impl BoxImpl for VideoPage {}
+}
@@ -60,6 +60,7 @@ pub mod imp {
 
     use crate::gui::filter::filter_item_object::FilterObject;
     use crate::gui::utility::Utility;
+    use crate::gui::window::Window;
 
     #[derive(CompositeTemplate, Default)]
     #[template(resource = "/ui/filter_item.ui")]
@@ -79,12 +80,28 @@ pub mod imp {
         fn bind_remove(&self) {
             let filter = &self.filter;
             let filter_group = &self.filter_group.clone();
+            let obj = self.obj();
             self.remove
-                .connect_clicked(clone!(@strong filter, @strong filter_group => move |_| {
+                .connect_clicked(clone!(@strong filter, @strong filter_group, @weak obj => move |_| {
+                    // Name the filter by its title pattern, or its channel pattern if it has none.
+                    let name = filter.borrow().as_ref().map(|f| {
+                        let title = f.property::<Option<String>>("title").unwrap_or_default();
+                        if title.is_empty() {
+                            f.property::<Option<String>>("channel").unwrap_or_default()
+                        } else {
+                            title
+                        }
+                    }).unwrap_or_default();
                     let filter = filter.borrow().as_ref().map(|s| s.filter()).flatten();
                     if let Some(filter) = filter {
-                        let filter_group = filter_group.borrow();
-                        filter_group.as_ref().expect("FilterGroup to be set up").lock().expect("FilterGroup to be lockable").remove(&filter);
+                        let filter_group = filter_group.borrow().clone().expect("FilterGroup to be set up");
+                        filter_group.lock().expect("FilterGroup to be lockable").remove(&filter);
+
+                        if let Some(window) = obj.root().and_then(|r| r.downcast::<Window>().ok()) {
+                            window.show_undo_toast(&gettextrs::gettext("Removed {}").replace("{}", &name), move || {
+                                filter_group.lock().expect("FilterGroup to be lockable").add(filter.clone());
+                            });
+                        }
                     }
                 }));
         }
@@ -112,11 +112,24 @@ pub mod imp {
                 about.show();
             }));
 
+            let action_queue = SimpleAction::new("queue", None);
+            action_queue.connect_activate(clone!(@weak obj => move |_, _| {
+                obj.window().show_queue();
+            }));
+
+            // This group shadows the actions of the window, forward the shortcuts window to it.
+            let action_help_overlay = SimpleAction::new("show-help-overlay", None);
+            action_help_overlay.connect_activate(clone!(@weak obj => move |_, _| {
+                let _ = WidgetExt::activate_action(&obj.window(), "win.show-help-overlay", None);
+            }));
+
             let actions = SimpleActionGroup::new();
             obj.insert_action_group("win", Some(&actions));
             actions.add_action(&action_import);
             actions.add_action(&action_settings);
             actions.add_action(&action_about);
+            actions.add_action(&action_help_overlay);
+            actions.add_action(&action_queue);
         }
     }
 
@@ -0,0 +1,364 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+gtk::glib::wrapper! {
+    pub struct InstanceGroup(ObjectSubclass<imp::InstanceGroup>)
+        @extends libadwaita::PreferencesGroup, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+pub mod imp {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use gdk::gio::Settings;
+    use gdk::glib::clone;
+    use gdk::glib::MainContext;
+    use gdk::glib::ParamSpec;
+    use gdk::glib::ParamSpecString;
+    use gdk::glib::Value;
+    use gdk::glib::PRIORITY_DEFAULT;
+    use glib::subclass::InitializingObject;
+    use gtk::glib;
+    use gtk::prelude::*;
+    use gtk::subclass::prelude::*;
+    use gtk::CompositeTemplate;
+    use libadwaita::prelude::*;
+    use libadwaita::subclass::prelude::PreferencesGroupImpl;
+    use libadwaita::ActionRow;
+    use libadwaita::EntryRow;
+    use libadwaita::ExpanderRow;
+    use once_cell::sync::Lazy;
+
+    use crate::instances;
+    use crate::youtube::Backend;
+
+    /// The state of a instance after checking it.
+    #[derive(Clone, Copy)]
+    enum Health {
+        Healthy(Duration),
+        Unreachable,
+    }
+
+    #[derive(CompositeTemplate)]
+    #[template(resource = "/ui/instance_group.ui")]
+    pub struct InstanceGroup {
+        #[template_child]
+        btn_check: TemplateChild<gtk::Button>,
+        #[template_child]
+        btn_load_public: TemplateChild<gtk::Button>,
+        #[template_child]
+        list_instances: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        entry_add: TemplateChild<EntryRow>,
+        #[template_child]
+        expander_public: TemplateChild<ExpanderRow>,
+
+        backend: RefCell<Option<String>>,
+        health: RefCell<HashMap<String, Health>>,
+        instance_rows: RefCell<Vec<ActionRow>>,
+        public_rows: RefCell<Vec<ActionRow>>,
+
+        settings: Settings,
+    }
+
+    impl InstanceGroup {
+        fn backend(&self) -> Backend {
+            self.backend
+                .borrow()
+                .as_ref()
+                .and_then(|b| b.parse().ok())
+                .unwrap_or(Backend::Piped)
+        }
+
+        fn instances(&self) -> Vec<String> {
+            self.settings
+                .strv(self.backend().instances_setting())
+                .iter()
+                .map(|i| i.to_string())
+                .collect()
+        }
+
+        fn set_instances(&self, instances: Vec<String>) {
+            let backend = self.backend();
+            let strv: Vec<&str> = instances.iter().map(|i| i.as_str()).collect();
+            if let Err(e) = self
+                .settings
+                .set_strv(backend.instances_setting(), strv.as_slice())
+            {
+                log::error!("Failed to save the instances of {}: {}", backend, e);
+            }
+            instances::set_configured(backend, &instances);
+            self.update_instance_rows();
+            self.update_public_rows();
+        }
+
+        fn add_instance(&self, instance: &str) {
+            let instance = instance.trim().trim_end_matches('/');
+            if instance.is_empty() {
+                return;
+            }
+            let mut instances = self.instances();
+            if !instances.iter().any(|i| i == instance) {
+                instances.push(instance.to_owned());
+                self.set_instances(instances);
+            }
+        }
+
+        fn remove_instance(&self, instance: &str) {
+            let mut instances = self.instances();
+            instances.retain(|i| i != instance);
+            self.set_instances(instances);
+        }
+
+        /// Move the instance by `offset` in the priority list.
+        fn move_instance(&self, instance: &str, offset: isize) {
+            let mut instances = self.instances();
+            if let Some(position) = instances.iter().position(|i| i == instance) {
+                let new_position = position as isize + offset;
+                if new_position >= 0 && (new_position as usize) < instances.len() {
+                    instances.swap(position, new_position as usize);
+                    self.set_instances(instances);
+                }
+            }
+        }
+
+        fn health_subtitle(&self, instance: &str) -> String {
+            match self.health.borrow().get(instance) {
+                Some(Health::Healthy(latency)) => {
+                    gettextrs::gettext("{} ms").replace("{}", &latency.as_millis().to_string())
+                }
+                Some(Health::Unreachable) => gettextrs::gettext("Unreachable"),
+                None => String::new(),
+            }
+        }
+
+        fn suffix_button(icon: &str, tooltip: &str) -> gtk::Button {
+            let button = gtk::Button::from_icon_name(icon);
+            button.set_valign(gtk::Align::Center);
+            button.set_tooltip_text(Some(tooltip));
+            button.add_css_class("flat");
+            button
+        }
+
+        fn update_instance_rows(&self) {
+            let obj = self.obj();
+            for row in self.instance_rows.borrow_mut().drain(..) {
+                self.list_instances.remove(&row);
+            }
+
+            let instances = self.instances();
+            let count = instances.len();
+            for (i, instance) in instances.into_iter().enumerate() {
+                let row = ActionRow::new();
+                row.set_title(&instance);
+                row.set_subtitle(&self.health_subtitle(&instance));
+
+                let btn_up = Self::suffix_button("go-up-symbolic", &gettextrs::gettext("Move Up"));
+                btn_up.set_sensitive(i > 0);
+                btn_up.connect_clicked(clone!(@weak obj, @strong instance => move |_| {
+                    obj.imp().move_instance(&instance, -1);
+                }));
+                let btn_down =
+                    Self::suffix_button("go-down-symbolic", &gettextrs::gettext("Move Down"));
+                btn_down.set_sensitive(i + 1 < count);
+                btn_down.connect_clicked(clone!(@weak obj, @strong instance => move |_| {
+                    obj.imp().move_instance(&instance, 1);
+                }));
+                let btn_remove =
+                    Self::suffix_button("user-trash-symbolic", &gettextrs::gettext("Remove"));
+                btn_remove.connect_clicked(clone!(@weak obj, @strong instance => move |_| {
+                    obj.imp().remove_instance(&instance);
+                }));
+
+                row.add_suffix(&btn_up);
+                row.add_suffix(&btn_down);
+                row.add_suffix(&btn_remove);
+                self.list_instances.append(&row);
+                self.instance_rows.borrow_mut().push(row);
+            }
+        }
+
+        fn update_public_rows(&self) {
+            let configured = self.instances();
+            for row in self.public_rows.borrow().iter() {
+                let instance = row.title();
+                row.set_subtitle(&self.health_subtitle(&instance));
+                row.set_sensitive(!configured.iter().any(|i| i == instance.as_str()));
+            }
+        }
+
+        fn set_public_instances(&self, public: Vec<String>) {
+            let obj = self.obj();
+            for row in self.public_rows.borrow_mut().drain(..) {
+                self.expander_public.remove(&row);
+            }
+            self.expander_public.set_subtitle(
+                &gettextrs::ngettext("{} instance", "{} instances", public.len() as u32)
+                    .replace("{}", &public.len().to_string()),
+            );
+
+            for instance in &public {
+                let row = ActionRow::new();
+                row.set_title(instance);
+                let btn_add = Self::suffix_button("list-add-symbolic", &gettextrs::gettext("Add"));
+                btn_add.connect_clicked(clone!(@weak obj, @strong instance => move |_| {
+                    obj.imp().add_instance(&instance);
+                }));
+                row.add_suffix(&btn_add);
+                self.expander_public.add_row(&row);
+                self.public_rows.borrow_mut().push(row);
+            }
+            self.update_public_rows();
+            self.check(public);
+        }
+
+        /// Check the health of the given instances and update the rows with the results.
+        fn check(&self, to_check: Vec<String>) {
+            let backend = self.backend();
+            let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+            for instance in to_check {
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    let health = match instances::check(backend, &instance).await {
+                        Some(latency) => Health::Healthy(latency),
+                        None => Health::Unreachable,
+                    };
+                    let _ = sender.send((instance, health));
+                });
+            }
+
+            let obj = self.obj();
+            receiver.attach(
+                None,
+                clone!(@weak obj => @default-return Continue(false), move |(instance, health)| {
+                    obj.imp().health.borrow_mut().insert(instance, health);
+                    obj.imp().update_instance_rows();
+                    obj.imp().update_public_rows();
+                    Continue(true)
+                }),
+            );
+        }
+
+        fn setup(&self) {
+            let obj = self.obj();
+            self.update_instance_rows();
+
+            self.entry_add
+                .connect_apply(clone!(@weak obj => move |entry| {
+                    obj.imp().add_instance(&entry.text());
+                    entry.set_text("");
+                }));
+
+            self.btn_check.connect_clicked(clone!(@weak obj => move |_| {
+                let mut to_check = obj.imp().instances();
+                to_check.extend(obj.imp().public_rows.borrow().iter().map(|r| r.title().to_string()));
+                obj.imp().check(to_check);
+            }));
+
+            self.btn_load_public.connect_clicked(clone!(@weak obj => move |_| {
+                let backend = obj.imp().backend();
+                obj.imp().expander_public.set_subtitle(&gettextrs::gettext("Loading…"));
+
+                let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+                tokio::spawn(async move {
+                    let _ = sender.send(instances::public(backend).await.map_err(|e| e.to_string()));
+                });
+                receiver.attach(
+                    None,
+                    clone!(@weak obj => @default-return Continue(false), move |result| {
+                        match result {
+                            Ok(public) => obj.imp().set_public_instances(public),
+                            Err(e) => {
+                                log::error!("Failed to load public instances: {}", e);
+                                obj.imp().expander_public.set_subtitle(&gettextrs::gettext("Failed to load the public instances"));
+                            }
+                        }
+                        Continue(false)
+                    }),
+                );
+            }));
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for InstanceGroup {
+        const NAME: &'static str = "TFInstanceGroup";
+        type Type = super::InstanceGroup;
+        type ParentType = libadwaita::PreferencesGroup;
+
+        fn new() -> Self {
+            Self {
+                btn_check: TemplateChild::default(),
+                btn_load_public: TemplateChild::default(),
+                list_instances: TemplateChild::default(),
+                entry_add: TemplateChild::default(),
+                expander_public: TemplateChild::default(),
+                backend: Default::default(),
+                health: Default::default(),
+                instance_rows: Default::default(),
+                public_rows: Default::default(),
+                settings: Settings::new(crate::config::APP_ID),
+            }
+        }
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for InstanceGroup {
+        fn properties() -> &'static [ParamSpec] {
+            static PROPERTIES: Lazy<Vec<ParamSpec>> =
+                Lazy::new(|| vec![ParamSpecString::builder("backend").construct_only().build()]);
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &Value, pspec: &ParamSpec) {
+            match pspec.name() {
+                "backend" => {
+                    let value: Option<String> =
+                        value.get().expect("Property backend of incorrect type");
+                    self.backend.replace(value);
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &ParamSpec) -> Value {
+            match pspec.name() {
+                "backend" => self.backend.borrow().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.setup();
+        }
+    }
+
+    impl WidgetImpl for InstanceGroup {}
+    impl PreferencesGroupImpl for InstanceGroup {}
+}
@@ -18,11 +18,15 @@
  *
  */
 
+mod command_error;
 mod feed;
 mod filter;
 mod header_bar;
 mod import_window;
+mod instance_group;
+mod notification;
 mod preferences_window;
+mod profile_group;
 mod subscription;
 mod utility;
 mod watch_later;
@@ -0,0 +1,96 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Desktop notifications about new videos.
+//!
+//! The actions of the notifications are registered on the application by the
+//! [Window][crate::gui::window::Window].
+
+use gdk::gio::Notification;
+use gdk::glib::ToVariant;
+use gdk::prelude::ObjectExt;
+use gtk::gio::prelude::ApplicationExt;
+
+use crate::gui::feed::feed_item_object::VideoObject;
+
+/// Send a notification for every channel with new videos.
+///
+/// The videos are expected to be sorted newest first. The buttons of a notification refer to the
+/// newest video of the channel.
+pub fn notify_new_videos(videos: &[VideoObject]) {
+    let app = match gtk::gio::Application::default() {
+        Some(app) => app,
+        None => return,
+    };
+
+    let mut channels: Vec<(String, Vec<&VideoObject>)> = vec![];
+    for video in videos {
+        let author = video
+            .property::<Option<String>>("author")
+            .unwrap_or_default();
+        match channels.iter_mut().find(|(a, _)| a == &author) {
+            Some((_, channel_videos)) => channel_videos.push(video),
+            None => channels.push((author, vec![video])),
+        }
+    }
+
+    for (author, channel_videos) in channels {
+        let titles: Vec<String> = channel_videos
+            .iter()
+            .map(|v| v.property::<Option<String>>("title").unwrap_or_default())
+            .collect();
+        let title = if titles.len() == 1 {
+            gettextrs::gettext("New Video from {}").replace("{}", &author)
+        } else {
+            gettextrs::ngettext(
+                "{count} New Video from {author}",
+                "{count} New Videos from {author}",
+                titles.len() as u32,
+            )
+            .replace("{count}", &titles.len().to_string())
+            .replace("{author}", &author)
+        };
+
+        let notification = Notification::new(&title);
+        notification.set_body(Some(&titles.join("\n")));
+        notification.set_default_action("app.show-feed");
+
+        let url = channel_videos[0]
+            .property::<Option<String>>("url")
+            .unwrap_or_default()
+            .to_variant();
+        notification.add_button_with_target_value(
+            &gettextrs::gettext("Open"),
+            "app.open-video",
+            Some(&url),
+        );
+        notification.add_button_with_target_value(
+            &gettextrs::gettext("Play"),
+            "app.play-video",
+            Some(&url),
+        );
+        notification.add_button_with_target_value(
+            &gettextrs::gettext("Watch Later"),
+            "app.watch-later",
+            Some(&url),
+        );
+
+        app.send_notification(Some(&format!("new-videos-{}", author)), &notification);
+    }
+}
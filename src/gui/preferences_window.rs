@@ -14,19 +14,43 @@ impl PreferencesWindow {
 }
 
 pub mod imp {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use gdk::gio;
     use gdk::gio::Settings;
     use gdk::gio::SettingsBindFlags;
+    use gdk::gio::SimpleAction;
+    use gdk::gio::SimpleActionGroup;
+    use gdk::glib::clone;
+    use gdk::glib::MainContext;
+    use gdk::glib::SourceId;
+    use gdk::glib::PRIORITY_DEFAULT;
     use glib::subclass::InitializingObject;
     use gtk::glib;
     use gtk::prelude::*;
     use gtk::subclass::prelude::*;
     use gtk::CompositeTemplate;
+    use gtk::SpinButton;
     use gtk::Switch;
     use libadwaita::subclass::prelude::AdwWindowImpl;
     use libadwaita::subclass::prelude::PreferencesWindowImpl;
+    use libadwaita::traits::ComboRowExt;
     use libadwaita::traits::PreferencesGroupExt;
+    use libadwaita::traits::PreferencesWindowExt;
+    use libadwaita::ComboRow;
     use libadwaita::EntryRow;
 
+    use crate::presets::{self, Preset};
+    use crate::youtube::Backend;
+
+    /// Checking whether the program is installed waits for the command to not change for this long.
+    const CHECK_INSTALLED_DELAY: Duration = Duration::from_millis(500);
+
+    /// The selectable refresh intervals in minutes, matching `combo_refresh_interval`.
+    const REFRESH_INTERVALS: [u32; 5] = [0, 15, 30, 60, 180];
+
     #[derive(CompositeTemplate)]
     #[template(resource = "/ui/preferences_window.ui")]
     pub struct PreferencesWindow {
@@ -34,48 +58,388 @@ pub mod imp {
         entry_player: TemplateChild<EntryRow>,
         #[template_child]
         entry_downloader: TemplateChild<EntryRow>,
+        #[template_child]
+        image_player_missing: TemplateChild<gtk::Image>,
+        #[template_child]
+        image_downloader_missing: TemplateChild<gtk::Image>,
+        #[template_child]
+        menu_player_presets: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        menu_downloader_presets: TemplateChild<gtk::MenuButton>,
+
+        #[template_child]
+        switch_mpv_ipc: TemplateChild<Switch>,
+        #[template_child]
+        switch_builtin_player: TemplateChild<Switch>,
 
         #[template_child]
-        entry_piped_api: TemplateChild<EntryRow>,
+        combo_youtube_backend: TemplateChild<ComboRow>,
 
         #[template_child]
         group_programs: TemplateChild<libadwaita::PreferencesGroup>,
 
         #[template_child]
-        switch_only_videos_yesterday: TemplateChild<Switch>,
+        combo_refresh_interval: TemplateChild<ComboRow>,
+        #[template_child]
+        switch_notify_new_videos: TemplateChild<Switch>,
+        #[template_child]
+        switch_run_in_background: TemplateChild<Switch>,
+        #[template_child]
+        switch_open_video_details: TemplateChild<Switch>,
+
+        #[template_child]
+        spin_fetch_concurrency: TemplateChild<SpinButton>,
+        #[template_child]
+        spin_fetch_timeout: TemplateChild<SpinButton>,
+        #[template_child]
+        spin_fetch_retries: TemplateChild<SpinButton>,
+        #[template_child]
+        spin_fetch_host_interval: TemplateChild<SpinButton>,
+
+        #[template_child]
+        label_cache_saved: TemplateChild<gtk::Label>,
+        #[template_child]
+        label_cache_downloaded: TemplateChild<gtk::Label>,
+        #[template_child]
+        label_cache_requests: TemplateChild<gtk::Label>,
 
         settings: Settings,
     }
 
     #[gtk::template_callbacks]
     impl PreferencesWindow {
+        #[template_callback]
+        fn handle_clear_cache(&self) {
+            crate::http_cache::clear();
+        }
+
+        fn init_cache_statistics(&self) {
+            let statistics = crate::http_cache::statistics();
+            self.label_cache_saved
+                .set_label(&glib::format_size(statistics.saved));
+            self.label_cache_downloaded
+                .set_label(&glib::format_size(statistics.downloaded));
+            self.label_cache_requests.set_label(
+                &gettextrs::gettext("{cached} of {total}")
+                    .replace(
+                        "{cached}",
+                        &(statistics.fresh + statistics.not_modified).to_string(),
+                    )
+                    .replace("{total}", &statistics.requests.to_string()),
+            );
+        }
+
+        #[template_callback]
+        fn handle_test_player(&self) {
+            self.test_command(self.entry_player.text().to_string());
+        }
+
+        #[template_callback]
+        fn handle_test_downloader(&self) {
+            self.test_command(self.entry_downloader.text().to_string());
+        }
+
+        /// Run the command in the background and show the result in a toast.
+        fn test_command(&self, command: String) {
+            let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+            std::thread::spawn(move || {
+                let _ = sender.send(crate::command::test(&command));
+            });
+            let obj = self.obj();
+            receiver.attach(
+                None,
+                clone!(@weak obj => @default-return Continue(false), move |result| {
+                    let title = match result {
+                        Ok(output) => gettextrs::gettext("The command works: {}").replace("{}", &output),
+                        Err(e) => gettextrs::gettext("The command failed: {}").replace("{}", &e.to_string()),
+                    };
+                    obj.add_toast(libadwaita::Toast::new(glib::markup_escape_text(&title).as_str()));
+                    Continue(false)
+                }),
+            );
+        }
+
+        /// Mark the entry as erroneous while it does not contain a valid command.
+        fn validate_command(entry: &EntryRow) {
+            match crate::command::parse(&entry.text()) {
+                Ok(_) => {
+                    entry.remove_css_class("error");
+                    entry.set_tooltip_text(None);
+                }
+                Err(e) => {
+                    entry.add_css_class("error");
+                    entry.set_tooltip_text(Some(&e.to_string()));
+                }
+            }
+        }
+
+        /// Show the warning while the program of the command in the entry is not installed.
+        fn check_installed(entry: &EntryRow, warning: &gtk::Image) {
+            let command = entry.text().to_string();
+            if let Some(program) = crate::command::parse(&command)
+                .ok()
+                .and_then(|args| args.into_iter().next())
+            {
+                warning.set_tooltip_text(Some(
+                    &gettextrs::gettext("{} was not found").replace("{}", &program),
+                ));
+            }
+
+            // Checking may take a while on Flatpak.
+            let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+            let checked = command.clone();
+            std::thread::spawn(move || {
+                let _ = sender.send(crate::presets::command_installed(&checked));
+            });
+            receiver.attach(
+                None,
+                clone!(@weak entry, @weak warning => @default-return Continue(false), move |installed| {
+                    // The command may have been changed while checking.
+                    if entry.text() == command {
+                        warning.set_visible(!installed);
+                    }
+                    Continue(false)
+                }),
+            );
+        }
+
+        fn init_command_setting(
+            &self,
+            env: &'static str,
+            settings: &'static str,
+            entry: EntryRow,
+            warning: gtk::Image,
+        ) {
+            self.init_string_setting(env, settings, entry.clone());
+            Self::validate_command(&entry);
+            Self::check_installed(&entry, &warning);
+            let pending: Rc<RefCell<Option<SourceId>>> = Default::default();
+            entry.connect_changed(move |entry| {
+                Self::validate_command(entry);
+                if let Some(source) = pending.take() {
+                    source.remove();
+                }
+                let source = glib::timeout_add_local_once(
+                    CHECK_INSTALLED_DELAY,
+                    clone!(@weak entry, @weak warning, @strong pending => move || {
+                        pending.take();
+                        Self::check_installed(&entry, &warning);
+                    }),
+                );
+                pending.replace(Some(source));
+            });
+        }
+
+        /// Offer the presets in the menu of the button, activating the action with their command.
+        fn set_presets(button: &gtk::MenuButton, action: &str, presets: Vec<Preset>) {
+            let menu = gio::Menu::new();
+            if presets.is_empty() {
+                menu.append(Some(&gettextrs::gettext("None Installed")), None);
+            }
+            for preset in presets {
+                let item = gio::MenuItem::new(Some(&preset.name), None);
+                item.set_action_and_target_value(Some(action), Some(&preset.command.to_variant()));
+                menu.append_item(&item);
+            }
+            button.set_menu_model(Some(&menu));
+        }
+
+        fn init_presets(&self) {
+            let actions = SimpleActionGroup::new();
+            for (name, entry) in [
+                ("player", self.entry_player.get()),
+                ("downloader", self.entry_downloader.get()),
+            ] {
+                let action = SimpleAction::new(name, Some(glib::VariantTy::STRING));
+                action.connect_activate(move |_, command| {
+                    if let Some(command) = command.and_then(|c| c.get::<String>()) {
+                        if entry.is_editable() {
+                            entry.set_text(&command);
+                        }
+                    }
+                });
+                actions.add_action(&action);
+            }
+            let obj = self.obj();
+            obj.insert_action_group("preset", Some(&actions));
+
+            // Probing may take a while on Flatpak.
+            let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+            std::thread::spawn(move || {
+                let _ = sender.send((
+                    presets::installed(presets::players()),
+                    presets::installed(presets::downloaders()),
+                ));
+            });
+            receiver.attach(
+                None,
+                clone!(@weak obj => @default-return Continue(false), move |(players, downloaders)| {
+                    Self::set_presets(&obj.imp().menu_player_presets, "preset.player", players);
+                    Self::set_presets(&obj.imp().menu_downloader_presets, "preset.downloader", downloaders);
+                    Continue(false)
+                }),
+            );
+        }
+
         fn init_flatpak(&self) {
-            self.group_programs.set_description(Some(&gettextrs::gettext("Note that on Flatpak, there are some more steps required when using a player external to the Flatpak. For more information, please consult the wiki.")));
+            let description = self.group_programs.description().unwrap_or_default();
+            let note = gettextrs::gettext("On Flatpak, the programs are run outside of the Flatpak and have to be installed on the system.");
+            self.group_programs
+                .set_description(Some(&format!("{} {}", description, note)));
         }
 
         fn init_string_setting(&self, env: &'static str, settings: &'static str, entry: EntryRow) {
-            let val_env = std::env::var_os(env);
+            let val_env = crate::environment::var(env);
             let val_settings = self.settings.string(settings);
             entry.set_text(&val_settings);
-            if val_env.is_some() && &val_env.unwrap() != val_settings.as_str() {
+            if val_env.is_some() && val_env.unwrap() != val_settings.as_str() {
                 entry.set_editable(false);
             }
             self.settings
                 .bind(settings, &entry, "text")
                 .flags(SettingsBindFlags::DEFAULT)
                 .build();
-            entry.connect_changed(move |entry| std::env::set_var(env, entry.text()));
+            entry.connect_changed(move |entry| crate::environment::set_var(env, entry.text()));
+        }
+
+        fn init_number_setting(&self, env: &'static str, settings: &'static str, spin: SpinButton) {
+            let val_env = crate::environment::var(env);
+            let val_settings = self.settings.uint(settings);
+            self.settings
+                .bind(settings, &spin, "value")
+                .flags(SettingsBindFlags::DEFAULT)
+                .build();
+            if val_env.is_some() && val_env.unwrap() != val_settings.to_string() {
+                spin.set_sensitive(false);
+            }
+            spin.connect_value_changed(move |spin| {
+                crate::environment::set_var(env, spin.value_as_int().to_string())
+            });
+        }
+
+        fn init_mpv_ipc(&self) {
+            let switch = self.switch_mpv_ipc.get();
+            let val_env = crate::environment::var("MPV_IPC");
+            let val_settings = self.settings.boolean("mpv-ipc");
+            self.settings
+                .bind("mpv-ipc", &switch, "active")
+                .flags(SettingsBindFlags::DEFAULT)
+                .build();
+            if val_env.is_some() && val_env.unwrap() != val_settings.to_string() {
+                switch.set_sensitive(false);
+            }
+            switch.connect_active_notify(|switch| {
+                crate::environment::set_var("MPV_IPC", switch.is_active().to_string())
+            });
+        }
+
+        fn init_youtube_backend(&self) {
+            let combo = self.combo_youtube_backend.get();
+            let backends = Backend::values();
+            let val_env = crate::environment::var("YOUTUBE_BACKEND");
+            let val_settings = self.settings.string("youtube-backend");
+            if let Some(position) = backends
+                .iter()
+                .position(|b| b.to_string() == val_settings.as_str())
+            {
+                combo.set_selected(position as u32);
+            }
+            if val_env.is_some() && val_env.unwrap() != val_settings.as_str() {
+                combo.set_sensitive(false);
+            }
+            combo.connect_selected_notify(
+                clone!(@strong self.settings as settings => move |combo| {
+                    if let Some(backend) = backends.get(combo.selected() as usize) {
+                        let _ = settings.set_string("youtube-backend", &backend.to_string());
+                        crate::environment::set_var("YOUTUBE_BACKEND", backend.to_string());
+                    }
+                }),
+            );
+        }
+
+        fn init_refresh_interval(&self) {
+            let combo = self.combo_refresh_interval.get();
+            let interval = self.settings.uint("refresh-interval");
+            let position = REFRESH_INTERVALS
+                .iter()
+                .position(|i| i >= &interval)
+                .unwrap_or(REFRESH_INTERVALS.len() - 1);
+            combo.set_selected(position as u32);
+            combo.connect_selected_notify(
+                clone!(@strong self.settings as settings => move |combo| {
+                    if let Some(interval) = REFRESH_INTERVALS.get(combo.selected() as usize) {
+                        let _ = settings.set_uint("refresh-interval", *interval);
+                    }
+                }),
+            );
         }
 
         fn init_settings(&self) {
-            self.init_string_setting("PLAYER", "player", self.entry_player.get());
-            self.init_string_setting("DOWNLOADER", "downloader", self.entry_downloader.get());
-            self.init_string_setting("PIPED_API_URL", "piped-url", self.entry_piped_api.get());
+            self.init_command_setting(
+                "PLAYER",
+                "player",
+                self.entry_player.get(),
+                self.image_player_missing.get(),
+            );
+            self.init_command_setting(
+                "DOWNLOADER",
+                "downloader",
+                self.entry_downloader.get(),
+                self.image_downloader_missing.get(),
+            );
+            self.init_presets();
+            self.init_mpv_ipc();
+            self.init_youtube_backend();
+            self.init_refresh_interval();
+            self.init_number_setting(
+                "FETCH_CONCURRENCY",
+                "fetch-concurrency",
+                self.spin_fetch_concurrency.get(),
+            );
+            self.init_number_setting(
+                "FETCH_TIMEOUT",
+                "fetch-timeout",
+                self.spin_fetch_timeout.get(),
+            );
+            self.init_number_setting(
+                "FETCH_RETRIES",
+                "fetch-retries",
+                self.spin_fetch_retries.get(),
+            );
+            self.init_number_setting(
+                "FETCH_HOST_INTERVAL",
+                "fetch-host-interval",
+                self.spin_fetch_host_interval.get(),
+            );
 
             self.settings
                 .bind(
-                    "only-videos-yesterday",
-                    &self.switch_only_videos_yesterday.get(),
+                    "builtin-player",
+                    &self.switch_builtin_player.get(),
+                    "active",
+                )
+                .flags(SettingsBindFlags::DEFAULT)
+                .build();
+            self.settings
+                .bind(
+                    "notify-new-videos",
+                    &self.switch_notify_new_videos.get(),
+                    "active",
+                )
+                .flags(SettingsBindFlags::DEFAULT)
+                .build();
+            self.settings
+                .bind(
+                    "run-in-background",
+                    &self.switch_run_in_background.get(),
+                    "active",
+                )
+                .flags(SettingsBindFlags::DEFAULT)
+                .build();
+            self.settings
+                .bind(
+                    "open-video-details",
+                    &self.switch_open_video_details.get(),
                     "active",
                 )
                 .flags(SettingsBindFlags::DEFAULT)
@@ -95,12 +459,30 @@ pub mod imp {
                 group_programs: TemplateChild::default(),
                 entry_player: TemplateChild::default(),
                 entry_downloader: TemplateChild::default(),
-                entry_piped_api: TemplateChild::default(),
-                switch_only_videos_yesterday: Default::default(),
+                image_player_missing: TemplateChild::default(),
+                image_downloader_missing: TemplateChild::default(),
+                menu_player_presets: TemplateChild::default(),
+                menu_downloader_presets: TemplateChild::default(),
+                switch_mpv_ipc: Default::default(),
+                switch_builtin_player: Default::default(),
+                combo_youtube_backend: TemplateChild::default(),
+                combo_refresh_interval: TemplateChild::default(),
+                switch_notify_new_videos: Default::default(),
+                switch_run_in_background: Default::default(),
+                switch_open_video_details: Default::default(),
+                spin_fetch_concurrency: Default::default(),
+                spin_fetch_timeout: Default::default(),
+                spin_fetch_retries: Default::default(),
+                spin_fetch_host_interval: Default::default(),
+                label_cache_saved: Default::default(),
+                label_cache_downloaded: Default::default(),
+                label_cache_requests: Default::default(),
             }
         }
 
         fn class_init(klass: &mut Self::Class) {
+            crate::gui::instance_group::InstanceGroup::ensure_type();
+            crate::gui::profile_group::ProfileGroup::ensure_type();
             Self::bind_template(klass);
             Self::bind_template_callbacks(klass);
         }
@@ -114,6 +496,7 @@ pub mod imp {
         fn constructed(&self) {
             self.parent_constructed();
             self.init_settings();
+            self.init_cache_statistics();
             if crate::config::FLATPAK {
                 self.init_flatpak();
             }
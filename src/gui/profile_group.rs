@@ -0,0 +1,275 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+gtk::glib::wrapper! {
+    pub struct ProfileGroup(ObjectSubclass<imp::ProfileGroup>)
+        @extends libadwaita::PreferencesGroup, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+pub mod imp {
+    use std::cell::RefCell;
+
+    use gdk::gio::Settings;
+    use gdk::glib::clone;
+    use glib::subclass::InitializingObject;
+    use gtk::glib;
+    use gtk::prelude::*;
+    use gtk::subclass::prelude::*;
+    use gtk::CompositeTemplate;
+    use libadwaita::prelude::*;
+    use libadwaita::subclass::prelude::PreferencesGroupImpl;
+    use libadwaita::ActionRow;
+    use libadwaita::EntryRow;
+    use libadwaita::ExpanderRow;
+
+    use crate::profiles::{self, Profile, ProfileTuple};
+
+    #[derive(CompositeTemplate)]
+    #[template(resource = "/ui/profile_group.ui")]
+    pub struct ProfileGroup {
+        #[template_child]
+        list_profiles: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        entry_add: TemplateChild<EntryRow>,
+
+        profile_rows: RefCell<Vec<ExpanderRow>>,
+
+        settings: Settings,
+    }
+
+    impl ProfileGroup {
+        fn profiles(&self) -> Vec<Profile> {
+            self.settings
+                .get::<Vec<ProfileTuple>>("player-profiles")
+                .into_iter()
+                .map(Into::into)
+                .collect()
+        }
+
+        /// Store the profiles without updating the rows.
+        fn store_profiles(&self, profiles: Vec<Profile>) {
+            profiles::set_configured(&profiles);
+            let tuples: Vec<ProfileTuple> = profiles.into_iter().map(Into::into).collect();
+            if let Err(e) = self.settings.set("player-profiles", tuples) {
+                log::error!("Failed to save the player profiles: {}", e);
+            }
+        }
+
+        fn default_profile(&self) -> String {
+            self.settings.string("player-profile").to_string()
+        }
+
+        fn set_default_profile(&self, name: Option<&str>) {
+            profiles::set_default_name(name);
+            if let Err(e) = self
+                .settings
+                .set_string("player-profile", name.unwrap_or_default())
+            {
+                log::error!("Failed to save the default player profile: {}", e);
+            }
+            self.update_rows();
+        }
+
+        fn add_profile(&self, name: &str) {
+            let name = name.trim();
+            if name.is_empty() {
+                return;
+            }
+            let mut profiles = self.profiles();
+            if !profiles.iter().any(|p| p.name == name) {
+                profiles.push(Profile {
+                    name: name.to_owned(),
+                    command: profiles::player(),
+                    platforms: vec![],
+                });
+                self.store_profiles(profiles);
+                self.update_rows();
+            }
+        }
+
+        fn remove_profile(&self, name: &str) {
+            let mut profiles = self.profiles();
+            profiles.retain(|p| p.name != name);
+            self.store_profiles(profiles);
+            if self.default_profile() == name {
+                self.set_default_profile(None);
+            } else {
+                self.update_rows();
+            }
+        }
+
+        /// Change the profile with the name without updating the rows.
+        fn edit_profile<F: FnOnce(&mut Profile)>(&self, name: &str, edit: F) {
+            let mut profiles = self.profiles();
+            if let Some(profile) = profiles.iter_mut().find(|p| p.name == name) {
+                edit(profile);
+                self.store_profiles(profiles);
+            }
+        }
+
+        fn suffix_button(icon: &str, tooltip: &str) -> gtk::Button {
+            let button = gtk::Button::from_icon_name(icon);
+            button.set_valign(gtk::Align::Center);
+            button.set_tooltip_text(Some(tooltip));
+            button.add_css_class("flat");
+            button
+        }
+
+        fn command_row(&self, row: &ExpanderRow, profile: &Profile) -> EntryRow {
+            let obj = self.obj();
+            let entry = EntryRow::new();
+            entry.set_title(&gettextrs::gettext("Command"));
+            entry.set_text(&profile.command);
+            entry.set_show_apply_button(true);
+            entry.connect_apply(
+                clone!(@weak obj, @weak row, @strong profile.name as name => move |entry| {
+                    let command = entry.text().to_string();
+                    match crate::command::parse(&command) {
+                        Ok(_) => {
+                            entry.remove_css_class("error");
+                            entry.set_tooltip_text(None);
+                            row.set_subtitle(&glib::markup_escape_text(&command));
+                            obj.imp().edit_profile(&name, |p| p.command = command);
+                        }
+                        Err(e) => {
+                            entry.add_css_class("error");
+                            entry.set_tooltip_text(Some(&e.to_string()));
+                        }
+                    }
+                }),
+            );
+            entry
+        }
+
+        fn platform_row(&self, profile: &Profile, platform: String) -> ActionRow {
+            let obj = self.obj();
+            let row = ActionRow::new();
+            row.set_title(&gettextrs::gettext("Only for {}").replace("{}", &platform));
+            let switch = gtk::Switch::new();
+            switch.set_valign(gtk::Align::Center);
+            switch.set_active(profile.platforms.contains(&platform));
+            switch.connect_active_notify(
+                clone!(@weak obj, @strong profile.name as name => move |switch| {
+                    let active = switch.is_active();
+                    obj.imp().edit_profile(&name, |profile| {
+                        profile.platforms.retain(|p| p != &platform);
+                        if active {
+                            profile.platforms.push(platform.clone());
+                        }
+                    });
+                }),
+            );
+            row.add_suffix(&switch);
+            row.set_activatable_widget(Some(&switch));
+            row
+        }
+
+        fn update_rows(&self) {
+            let obj = self.obj();
+            for row in self.profile_rows.borrow_mut().drain(..) {
+                self.list_profiles.remove(&row);
+            }
+
+            let default = self.default_profile();
+            for profile in self.profiles() {
+                let is_default = profile.name == default;
+                let row = ExpanderRow::new();
+                row.set_title(&glib::markup_escape_text(&profile.name));
+                row.set_subtitle(&glib::markup_escape_text(&profile.command));
+
+                let btn_default = if is_default {
+                    Self::suffix_button("starred-symbolic", &gettextrs::gettext("Default"))
+                } else {
+                    Self::suffix_button(
+                        "non-starred-symbolic",
+                        &gettextrs::gettext("Use as Default"),
+                    )
+                };
+                btn_default.connect_clicked(
+                    clone!(@weak obj, @strong profile.name as name => move |_| {
+                        obj.imp().set_default_profile((!is_default).then_some(name.as_str()));
+                    }),
+                );
+                let btn_remove =
+                    Self::suffix_button("user-trash-symbolic", &gettextrs::gettext("Remove"));
+                btn_remove.connect_clicked(
+                    clone!(@weak obj, @strong profile.name as name => move |_| {
+                        obj.imp().remove_profile(&name);
+                    }),
+                );
+                row.add_action(&btn_default);
+                row.add_action(&btn_remove);
+
+                row.add_row(&self.command_row(&row, &profile));
+                for platform in profiles::platforms() {
+                    row.add_row(&self.platform_row(&profile, platform));
+                }
+
+                self.list_profiles.append(&row);
+                self.profile_rows.borrow_mut().push(row);
+            }
+        }
+
+        fn setup(&self) {
+            let obj = self.obj();
+            self.update_rows();
+
+            self.entry_add
+                .connect_apply(clone!(@weak obj => move |entry| {
+                    obj.imp().add_profile(&entry.text());
+                    entry.set_text("");
+                }));
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ProfileGroup {
+        const NAME: &'static str = "TFProfileGroup";
+        type Type = super::ProfileGroup;
+        type ParentType = libadwaita::PreferencesGroup;
+
+        fn new() -> Self {
+            Self {
+                list_profiles: TemplateChild::default(),
+                entry_add: TemplateChild::default(),
+                profile_rows: Default::default(),
+                settings: Settings::new(crate::config::APP_ID),
+            }
+        }
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ProfileGroup {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.setup();
+        }
+    }
+
+    impl WidgetImpl for ProfileGroup {}
+    impl PreferencesGroupImpl for ProfileGroup {}
+}
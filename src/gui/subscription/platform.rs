@@ -24,6 +24,8 @@ use gdk::glib::Object;
 use gdk::subclass::prelude::ObjectSubclassIsExt;
 use tf_join::Platform;
 
+use crate::provider::Provider;
+
 macro_rules! str_prop {
     ( $x:expr ) => {
         ParamSpecString::builder($x).build()
@@ -74,9 +76,21 @@ impl PlatformObject {
         s
     }
 
+    pub fn new_provider(provider: Provider) -> Self {
+        let s: Self = Object::builder::<Self>()
+            .property("name", provider.name())
+            .build();
+        s.imp().provider.swap(&RefCell::new(Some(provider)));
+        s
+    }
+
     pub fn platform(&self) -> Option<Platform> {
         self.imp().platform.borrow().clone()
     }
+
+    pub fn provider(&self) -> Option<Provider> {
+        self.imp().provider.borrow().clone()
+    }
 }
 
 mod imp {
@@ -84,6 +98,8 @@ mod imp {
     use std::cell::RefCell;
     use tf_join::Platform;
 
+    use crate::provider::Provider;
+
     use gdk::{
         glib::{ParamSpec, ParamSpecString, Value},
         prelude::ToValue,
@@ -96,6 +112,7 @@ mod imp {
         name: RefCell<Option<String>>,
 
         pub(super) platform: RefCell<Option<Platform>>,
+        pub(super) provider: RefCell<Option<Provider>>,
     }
 
     #[glib::object_subclass]
@@ -22,6 +22,8 @@ use gdk::subclass::prelude::ObjectSubclassIsExt;
 use gtk::glib::Object;
 use tf_join::AnySubscriptionList;
 
+use crate::provider::ProviderSubscriptionList;
+
 gtk::glib::wrapper! {
     pub struct SubscriptionItem(ObjectSubclass<imp::SubscriptionItem>)
         @extends gtk::Box, gtk::Widget,
@@ -30,9 +32,15 @@ gtk::glib::wrapper! {
 }
 
 impl SubscriptionItem {
-    pub fn new(subscription_list: AnySubscriptionList) -> Self {
+    pub fn new(
+        subscription_list: AnySubscriptionList,
+        provider_subscription_list: ProviderSubscriptionList,
+    ) -> Self {
         let s: Self = Object::builder().build();
         s.imp().subscription_list.replace(Some(subscription_list));
+        s.imp()
+            .provider_subscription_list
+            .replace(Some(provider_subscription_list));
         s
     }
 }
@@ -55,6 +63,8 @@ pub mod imp {
 
     use crate::gui::subscription::subscription_item_object::SubscriptionObject;
     use crate::gui::utility::Utility;
+    use crate::gui::window::Window;
+    use crate::provider::ProviderSubscriptionList;
 
     #[derive(CompositeTemplate, Default)]
     #[template(resource = "/ui/subscription_item.ui")]
@@ -68,6 +78,7 @@ pub mod imp {
 
         subscription: RefCell<Option<SubscriptionObject>>,
         pub(super) subscription_list: RefCell<Option<AnySubscriptionList>>,
+        pub(super) provider_subscription_list: RefCell<Option<ProviderSubscriptionList>>,
     }
 
     #[gtk::template_callbacks]
@@ -75,12 +86,29 @@ pub mod imp {
         fn bind_remove(&self) {
             let subscription = &self.subscription;
             let subscription_list = &self.subscription_list;
+            let provider_subscription_list = &self.provider_subscription_list;
+            let obj = self.obj();
             self.remove.connect_clicked(
-                clone!(@strong subscription, @strong subscription_list => move |_| {
+                clone!(@strong subscription, @strong subscription_list, @strong provider_subscription_list, @weak obj => move |_| {
+                    let name = subscription.borrow().as_ref().and_then(|s| s.property::<Option<String>>("name")).unwrap_or_default();
+                    let window = obj.root().and_then(|r| r.downcast::<Window>().ok());
+                    let title = gettextrs::gettext("Removed {}").replace("{}", &name);
+
+                    let provider_subscription = subscription.borrow().as_ref().and_then(|s| s.provider_subscription());
+                    if let Some(provider_subscription) = provider_subscription {
+                        let provider_subscription_list = provider_subscription_list.borrow().clone().unwrap();
+                        provider_subscription_list.remove(provider_subscription.clone());
+                        if let Some(window) = &window {
+                            window.show_undo_toast(&title, move || provider_subscription_list.add(provider_subscription.clone()));
+                        }
+                    }
                     let subscription = subscription.borrow().as_ref().map(|s| s.subscription()).flatten();
                     if let Some(subscription) = subscription {
-                        let mut subscription_list = subscription_list.borrow_mut();
-                        subscription_list.as_mut().unwrap().remove(subscription);
+                        let subscription_list = subscription_list.borrow().clone().unwrap();
+                        subscription_list.remove(subscription.clone());
+                        if let Some(window) = &window {
+                            window.show_undo_toast(&title, move || subscription_list.add(subscription.clone()));
+                        }
                     }
                 }),
             );
@@ -26,6 +26,8 @@ use gdk_pixbuf::prelude::ObjectExt;
 use tf_core::Subscription;
 use tf_join::AnySubscription;
 
+use crate::provider::ProviderSubscription;
+
 macro_rules! str_prop {
     ( $x:expr ) => {
         ParamSpecString::builder($x).build()
@@ -77,10 +79,25 @@ impl SubscriptionObject {
         s
     }
 
+    pub fn from_provider(subscription: ProviderSubscription) -> Self {
+        let s: Self = Object::builder::<Self>()
+            .property("name", subscription.to_string())
+            .property("platform", subscription.provider())
+            .build();
+        s.imp()
+            .provider_subscription
+            .swap(&RefCell::new(Some(subscription)));
+        s
+    }
+
     pub fn subscription(&self) -> Option<AnySubscription> {
         self.imp().subscription.borrow().clone()
     }
 
+    pub fn provider_subscription(&self) -> Option<ProviderSubscription> {
+        self.imp().provider_subscription.borrow().clone()
+    }
+
     pub fn update_name(&self, sub: &AnySubscription) {
         self.set_property("name", sub.name());
     }
@@ -91,6 +108,8 @@ mod imp {
     use std::cell::RefCell;
     use tf_join::AnySubscription;
 
+    use crate::provider::ProviderSubscription;
+
     use gdk::{
         glib::{ParamSpec, ParamSpecString, Value},
         prelude::ToValue,
@@ -104,6 +123,7 @@ mod imp {
         platform: RefCell<Option<String>>,
 
         pub(super) subscription: RefCell<Option<AnySubscription>>,
+        pub(super) provider_subscription: RefCell<Option<ProviderSubscription>>,
     }
 
     #[glib::object_subclass]
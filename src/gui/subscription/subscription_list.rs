@@ -27,6 +27,7 @@ use gtk::{traits::SorterExt, SorterChange};
 use tf_join::{AnySubscription, AnySubscriptionList};
 
 use super::subscription_item_object::SubscriptionObject;
+use crate::provider::ProviderSubscriptionList;
 
 gtk::glib::wrapper! {
     pub struct SubscriptionList(ObjectSubclass<imp::SubscriptionList>)
@@ -64,10 +65,11 @@ impl SubscriptionList {
         let model = imp.model.borrow();
 
         if let Some(idx) = model.snapshot().into_iter().position(|i| {
-            i.downcast::<SubscriptionObject>()
-                .expect("Items should be of type SubscriptionObject")
-                .subscription()
-                == new_item.subscription()
+            let i = i
+                .downcast::<SubscriptionObject>()
+                .expect("Items should be of type SubscriptionObject");
+            i.subscription() == new_item.subscription()
+                && i.provider_subscription() == new_item.provider_subscription()
         }) {
             model.remove(idx as u32);
         }
@@ -89,10 +91,17 @@ impl SubscriptionList {
             .for_each(|i| i.update_name(&sub))
     }
 
-    pub fn set_subscription_list(&self, subscription_list: AnySubscriptionList) {
+    pub fn set_subscription_list(
+        &self,
+        subscription_list: AnySubscriptionList,
+        provider_subscription_list: ProviderSubscriptionList,
+    ) {
         self.imp()
             .any_subscription_list
             .replace(Some(subscription_list));
+        self.imp()
+            .provider_subscription_list
+            .replace(Some(provider_subscription_list));
         self.imp().setup(&self);
     }
 }
@@ -130,6 +139,11 @@ pub mod imp {
 
     use crate::gui::subscription::subscription_item::SubscriptionItem;
     use crate::gui::subscription::subscription_item_object::SubscriptionObject;
+    use crate::provider::ProviderSubscriptionEvent;
+    use crate::provider::ProviderSubscriptionList;
+
+    /// An observer kept alive for as long as the widget lives.
+    type ObserverHandle<T> = RefCell<Option<Arc<Mutex<Box<dyn Observer<T> + Send>>>>>;
 
     #[derive(CompositeTemplate, Default)]
     #[template(resource = "/ui/subscription_list.ui")]
@@ -141,8 +155,9 @@ pub mod imp {
         pub(super) sorter: RefCell<Option<CustomSorter>>,
 
         pub(super) any_subscription_list: RefCell<Option<AnySubscriptionList>>,
-        _subscription_observer:
-            RefCell<Option<Arc<Mutex<Box<dyn Observer<SubscriptionEvent> + Send>>>>>,
+        pub(super) provider_subscription_list: RefCell<Option<ProviderSubscriptionList>>,
+        _subscription_observer: ObserverHandle<SubscriptionEvent>,
+        _provider_subscription_observer: ObserverHandle<ProviderSubscriptionEvent>,
     }
 
     impl SubscriptionList {
@@ -161,15 +176,52 @@ pub mod imp {
             })
                 as Box<dyn Observer<SubscriptionEvent> + Send>));
 
+            let mut provider_subscription_list = self
+                .provider_subscription_list
+                .borrow()
+                .clone()
+                .expect("ProviderSubscriptionList should be set up");
+
+            let (provider_sender, provider_receiver) = MainContext::channel(PRIORITY_DEFAULT);
+
+            let provider_observer =
+                Arc::new(Mutex::new(Box::new(ProviderSubscriptionPageObserver {
+                    sender: provider_sender,
+                })
+                    as Box<dyn Observer<ProviderSubscriptionEvent> + Send>));
+
             let existing: Vec<SubscriptionObject> = any_subscription_list
                 .iter()
                 .map(|v| SubscriptionObject::new(v.clone()))
+                .chain(
+                    provider_subscription_list
+                        .iter()
+                        .map(SubscriptionObject::from_provider),
+                )
                 .collect();
 
             any_subscription_list.attach(Arc::downgrade(&observer));
             self._subscription_observer.replace(Some(observer));
+            provider_subscription_list.attach(Arc::downgrade(&provider_observer));
+            self._provider_subscription_observer
+                .replace(Some(provider_observer));
             obj.set(existing);
 
+            provider_receiver.attach(
+                None,
+                clone!(@strong obj => move |subscription_event| {
+                    match subscription_event {
+                        ProviderSubscriptionEvent::Add(s) => {
+                            obj.add(SubscriptionObject::from_provider(s));
+                        }
+                        ProviderSubscriptionEvent::Remove(s) => {
+                            obj.remove(SubscriptionObject::from_provider(s));
+                        }
+                    }
+                    Continue(true)
+                }),
+            );
+
             receiver.attach(
                 None,
                 clone!(@strong obj => move |subscription_event| {
@@ -230,9 +282,14 @@ pub mod imp {
                 .borrow()
                 .clone()
                 .expect("AnySubscriptionList should be set up");
+            let provider_subscription_list = self
+                .provider_subscription_list
+                .borrow()
+                .clone()
+                .expect("ProviderSubscriptionList should be set up");
             let instance = self.obj();
             factory.connect_setup(clone!(@strong instance, @strong sorter => move |_, list_item| {
-                let subscription_item = SubscriptionItem::new(any_subscription_list.clone());
+                let subscription_item = SubscriptionItem::new(any_subscription_list.clone(), provider_subscription_list.clone());
                 list_item.set_child(Some(&subscription_item));
 
                 subscription_item.connect_local("go-to-videos", false, clone!(@strong instance => move |args| {
@@ -321,4 +378,14 @@ pub mod imp {
             let _ = self.sender.send(message);
         }
     }
+
+    pub struct ProviderSubscriptionPageObserver {
+        sender: Sender<ProviderSubscriptionEvent>,
+    }
+
+    impl Observer<ProviderSubscriptionEvent> for ProviderSubscriptionPageObserver {
+        fn notify(&mut self, message: ProviderSubscriptionEvent) {
+            let _ = self.sender.send(message);
+        }
+    }
 }
@@ -24,6 +24,8 @@ use gtk::traits::WidgetExt;
 use tf_join::{AnySubscriptionList, AnyVideo};
 use tf_playlist::PlaylistManager;
 
+use crate::provider::ProviderSubscriptionList;
+
 gtk::glib::wrapper! {
     pub struct SubscriptionPage(ObjectSubclass<imp::SubscriptionPage>)
         @extends gtk::Box, gtk::Widget,
@@ -40,19 +42,27 @@ impl SubscriptionPage {
     pub fn set_subscription_list(
         &self,
         subscription_list: AnySubscriptionList,
+        provider_subscription_list: ProviderSubscriptionList,
         playlist_manager: PlaylistManager<String, AnyVideo>,
     ) {
         self.imp()
             .any_subscription_list
             .replace(Some(subscription_list.clone()));
+        self.imp()
+            .provider_subscription_list
+            .replace(Some(provider_subscription_list.clone()));
         self.imp()
             .subscription_list
             .get()
-            .set_subscription_list(subscription_list);
+            .set_subscription_list(subscription_list, provider_subscription_list);
         self.imp()
             .subscription_video_list
             .get()
             .set_playlist_manager(playlist_manager);
+        self.imp()
+            .subscription_video_list
+            .get()
+            .bind_filter_settings();
     }
 
     fn window(&self) -> crate::gui::window::Window {
@@ -82,12 +92,10 @@ pub mod imp {
 
     use gtk::CompositeTemplate;
     use once_cell::sync::Lazy;
-    use tf_core::Generator;
     use tf_join::AnySubscriptionList;
     use tf_join::Platform;
     use tf_lbry::LbrySubscription;
     use tf_pt::PTSubscription;
-    use tf_yt::YTSubscription;
 
     use crate::gui::feed::feed_item_object::VideoObject;
     use crate::gui::feed::feed_list::FeedList;
@@ -95,6 +103,10 @@ pub mod imp {
     use crate::gui::subscription::subscription_item_object::SubscriptionObject;
     use crate::gui::subscription::subscription_list::SubscriptionList;
     use crate::gui::utility::Utility;
+    use crate::provider::Provider;
+    use crate::provider::ProviderSubscription;
+    use crate::provider::ProviderSubscriptionList;
+    use crate::scheduler::Scheduler;
 
     #[derive(CompositeTemplate, Default)]
     #[template(resource = "/ui/subscription_page.ui")]
@@ -121,6 +133,7 @@ pub mod imp {
         pub(super) subscription_video_list: TemplateChild<FeedList>,
 
         pub(super) any_subscription_list: RefCell<Option<AnySubscriptionList>>,
+        pub(super) provider_subscription_list: RefCell<Option<ProviderSubscriptionList>>,
     }
 
     impl SubscriptionPage {
@@ -167,6 +180,14 @@ pub mod imp {
                     PlatformObject::new(Platform::Peertube),
                 ],
             );
+            model.splice(
+                model.n_items(),
+                0,
+                &Provider::all()
+                    .into_iter()
+                    .map(PlatformObject::new_provider)
+                    .collect::<Vec<_>>(),
+            );
             self.dropdown_platform.set_model(Some(&model));
         }
     }
@@ -183,24 +204,36 @@ pub mod imp {
             let in_url = &self.entry_url;
             let in_name_id = &self.entry_name_id;
 
-            let platform = in_platform
+            let platform_object = in_platform
                 .selected_item()
                 .expect("Something has to be selected.")
                 .downcast::<PlatformObject>()
-                .expect("Dropdown items should be of type PlatformObject.")
-                .platform()
-                .expect("The platform has to be set up.");
+                .expect("Dropdown items should be of type PlatformObject.");
             let url = in_url.text();
             let name_id = in_name_id.text();
 
             in_url.set_text("");
             in_name_id.set_text("");
 
+            if let Some(provider) = platform_object.provider() {
+                self.provider_subscription_list
+                    .borrow()
+                    .as_ref()
+                    .expect("ProviderSubscriptionList should be set up")
+                    .add(ProviderSubscription::new(provider.name(), name_id));
+                self.obj().emit_by_name::<()>("subscription-added", &[]);
+                return;
+            }
+
+            let platform = platform_object
+                .platform()
+                .expect("The platform has to be set up.");
+
             let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
             let sender = sender.clone();
             tokio::spawn(async move {
                 let subscription = match platform {
-                    Platform::Youtube => YTSubscription::try_from_search(&name_id)
+                    Platform::Youtube => crate::youtube::search_channel(&name_id)
                         .await
                         .map(|s| s.into()),
                     Platform::Peertube => Some(PTSubscription::new(&url, &name_id).into()),
@@ -235,29 +268,39 @@ pub mod imp {
             log::debug!(
                 "Going to videos of subscription {}",
                 subscription
-                    .subscription()
-                    .expect("SubscriptionObject to have value")
+                    .property::<Option<String>>("name")
+                    .unwrap_or_default()
             );
             self.subscription_stack.set_visible_child_name("page-vid");
             let joiner = tf_join::Joiner::new();
-            joiner.subscription_list().add(
-                subscription
-                    .subscription()
-                    .expect("SubscriptionObject to have value"),
-            );
+            let provider_subscription_list = ProviderSubscriptionList::default();
+            if let Some(subscription) = subscription.subscription() {
+                joiner.subscription_list().add(subscription);
+            }
+            if let Some(subscription) = subscription.provider_subscription() {
+                provider_subscription_list.add(subscription);
+            }
 
             let error_store = tf_core::ErrorStore::new();
 
             let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
             tokio::spawn(async move {
-                let videos = joiner.generate(&error_store).await;
-                let _ = sender.send(videos);
+                let scheduler = Scheduler::from_env();
+                let (videos, provider_videos) = futures::join!(
+                    crate::generator::generate(&joiner, &error_store, &scheduler),
+                    provider_subscription_list.generate(&error_store, joiner.filters(), &scheduler)
+                );
+                let _ = sender.send((videos, provider_videos));
             });
             let obj = self.obj();
             receiver.attach(
                 None,
-                clone!(@strong obj as s => @default-return Continue(false), move |videos| {
-                    let video_objects = videos.into_iter().map(VideoObject::new).collect::<Vec<_>>();
+                clone!(@strong obj as s => @default-return Continue(false), move |(videos, provider_videos)| {
+                    let video_objects = videos
+                        .into_iter()
+                        .map(VideoObject::new)
+                        .chain(provider_videos.into_iter().map(VideoObject::from_provider))
+                        .collect::<Vec<_>>();
                     s.imp().subscription_video_list.get().set_items(video_objects);
                     Continue(true)
                 }),
@@ -27,6 +27,11 @@ impl Utility {
         b1 || b2
     }
 
+    #[template_callback]
+    fn and(b1: bool, b2: bool) -> bool {
+        b1 && b2
+    }
+
     #[template_callback]
     fn not(#[rest] values: &[gtk::glib::Value]) -> bool {
         !values[0]
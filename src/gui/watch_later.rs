@@ -34,6 +34,11 @@ impl WatchLaterPage {
         self.imp().playlist_manager.replace(Some(playlist_manager));
         self.imp().setup();
     }
+
+    /// Show or hide the search bar.
+    pub fn toggle_search(&self) {
+        self.imp().feed_page.toggle_search();
+    }
 }
 
 pub mod imp {
@@ -59,6 +64,7 @@ pub mod imp {
     use crate::gui::feed::feed_item_object::VideoObject;
     use crate::gui::feed::feed_list::FeedList;
     use crate::gui::utility::Utility;
+    use crate::gui::window::Window;
 
     #[derive(CompositeTemplate, Default)]
     #[template(resource = "/ui/watch_later.ui")]
@@ -120,6 +126,17 @@ pub mod imp {
         }
     }
 
+    #[gtk::template_callbacks]
+    impl WatchLaterPage {
+        #[template_callback]
+        fn handle_queue_all(&self) {
+            let window = self.obj().root().and_then(|r| r.downcast::<Window>().ok());
+            if let Some(window) = window {
+                window.queue(&self.feed_page.items());
+            }
+        }
+    }
+
     #[glib::object_subclass]
     impl ObjectSubclass for WatchLaterPage {
         const NAME: &'static str = "TFWatchLaterPage";
@@ -128,6 +145,7 @@ pub mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            Self::bind_template_callbacks(klass);
             Utility::bind_template_callbacks(klass);
         }
 
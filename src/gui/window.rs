@@ -21,9 +21,16 @@
 use gdk::subclass::prelude::ObjectSubclassIsExt;
 use gdk_pixbuf::prelude::SettingsExt;
 use gtk::{
-    glib::Object,
+    gio::prelude::ListModelExt,
+    glib::{self, clone, prelude::Cast, Continue, MainContext, Object, PRIORITY_DEFAULT},
+    prelude::ObjectExt,
     traits::{GtkWindowExt, WidgetExt},
 };
+use libadwaita::NavigationDirection;
+
+use crate::gui::feed::comments_window::CommentsWindow;
+use crate::gui::feed::feed_item_object::VideoObject;
+use crate::gui::feed::queue_window::QueueWindow;
 
 fn setup_joiner() -> tf_join::Joiner {
     let joiner = tf_join::Joiner::new();
@@ -39,15 +46,165 @@ gtk::glib::wrapper! {
 
 impl Window {
     pub fn new(app: &gtk::Application) -> Self {
-        Object::builder::<Self>()
+        let s: Self = Object::builder::<Self>()
             .property("application", app)
-            .build()
+            .build();
+        s.imp().setup_app_actions(app);
+        s
     }
 
     pub fn reload(&self) {
         let _ = self.activate_action("win.reload", None);
     }
 
+    /// Show a toast with the given title and a button calling `undo`.
+    pub fn show_undo_toast<F: Fn() + 'static>(&self, title: &str, undo: F) {
+        let toast = libadwaita::Toast::builder()
+            .title(gtk::glib::markup_escape_text(title).as_str())
+            .button_label(gettextrs::gettext("Undo"))
+            .build();
+        toast.connect_button_clicked(move |_| undo());
+        self.imp().toast_overlay.add_toast(toast);
+    }
+
+    /// Show a toast with the given title.
+    pub fn show_toast(&self, title: &str) {
+        self.imp().toast_overlay.add_toast(libadwaita::Toast::new(
+            gtk::glib::markup_escape_text(title).as_str(),
+        ));
+    }
+
+    /// Show the details of the given video.
+    pub fn show_video(&self, video: &VideoObject) {
+        let imp = self.imp();
+        imp.video_page.set_video(video);
+        imp.leaflet.set_visible_child(&imp.video_page.get());
+    }
+
+    /// Go back from the details of a video.
+    pub fn hide_video(&self) {
+        self.imp().leaflet.navigate(NavigationDirection::Back);
+    }
+
+    /// Play the video, in the built-in player if it is enabled.
+    pub fn play_video(&self, video: &VideoObject) {
+        if self.imp().settings.boolean("builtin-player") {
+            self.play_builtin(video);
+        } else {
+            video.play();
+        }
+    }
+
+    /// Play the video in the built-in player if it can play it, with the configured player
+    /// otherwise.
+    pub fn play_builtin(&self, video: &VideoObject) {
+        let url = video.property::<Option<String>>("url").unwrap_or_default();
+        let platform = video.property::<Option<String>>("platform");
+        let local_path = video.property::<Option<String>>("local-path");
+        let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+        tokio::spawn(async move {
+            let _ = sender.send(crate::media::uri(url, platform, local_path).await);
+        });
+        receiver.attach(
+            None,
+            clone!(@weak self as obj, @weak video => @default-return Continue(false), move |uri| {
+                match uri {
+                    Some(uri) => obj.show_player(&video, &uri),
+                    None => {
+                        log::debug!("The video cannot be played in the application, using the player");
+                        video.play();
+                    }
+                }
+                Continue(false)
+            }),
+        );
+    }
+
+    fn show_player(&self, video: &VideoObject, uri: &str) {
+        let imp = self.imp();
+        // Going back from the player leads to where it was opened from.
+        let from_details =
+            imp.leaflet.visible_child().as_ref() == Some(imp.video_page.upcast_ref());
+        imp.leaflet
+            .page(&imp.video_page.get())
+            .set_navigatable(from_details);
+        imp.player_page.play(video, uri);
+        imp.leaflet.set_visible_child(&imp.player_page.get());
+    }
+
+    /// Go back from the built-in player.
+    pub fn hide_player(&self) {
+        let imp = self.imp();
+        imp.player_page.stop();
+        imp.leaflet.navigate(NavigationDirection::Back);
+    }
+
+    /// Show the comments of the given video in a new window.
+    pub fn show_comments(&self, video: &VideoObject) {
+        let comments = CommentsWindow::new(video);
+        comments.set_transient_for(Some(self));
+        comments.present();
+    }
+
+    /// Add the videos to the end of the play queue, skipping the ones already queued.
+    pub fn queue(&self, videos: &[VideoObject]) {
+        let queue = &self.imp().play_queue;
+        let queued: Vec<VideoObject> = (0..queue.n_items())
+            .filter_map(|i| queue.item(i).and_then(|v| v.downcast::<VideoObject>().ok()))
+            .collect();
+        let new: Vec<VideoObject> = videos
+            .iter()
+            .filter(|v| !queued.iter().any(|q| q.same_video(v)))
+            .cloned()
+            .collect();
+        queue.splice(queue.n_items(), 0, &new);
+
+        let title = gettextrs::ngettext(
+            "Added {} video to the queue",
+            "Added {} videos to the queue",
+            new.len() as u32,
+        )
+        .replace("{}", &new.len().to_string());
+        self.imp()
+            .toast_overlay
+            .add_toast(libadwaita::Toast::new(&title));
+    }
+
+    /// Queue all unwatched videos in the feed from the channel of the given video, oldest first.
+    pub fn queue_channel(&self, video: &VideoObject) {
+        let author = video.property::<Option<String>>("author");
+        let platform = video.property::<Option<String>>("platform");
+        let mut videos: Vec<VideoObject> = self
+            .imp()
+            .feed_page
+            .videos()
+            .into_iter()
+            .filter(|v| {
+                !v.property::<bool>("watched")
+                    && v.property::<Option<String>>("author") == author
+                    && v.property::<Option<String>>("platform") == platform
+            })
+            .collect();
+        videos.sort_by_key(|v| v.uploaded());
+        self.queue(&videos);
+    }
+
+    /// Show the play queue in a new window.
+    pub fn show_queue(&self) {
+        let window = QueueWindow::new(&self.imp().play_queue);
+        window.set_transient_for(Some(self));
+        window.present();
+    }
+
+    /// Either play the video or show its details, depending on the settings.
+    pub fn activate_video(&self, video: &VideoObject) {
+        if self.imp().settings.boolean("open-video-details") {
+            self.show_video(video);
+        } else {
+            self.play_video(video);
+        }
+    }
+
     fn save_window_size(&self) -> Result<(), gtk::glib::BoolError> {
         let imp = self.imp();
 
@@ -80,10 +237,13 @@ impl Window {
 pub mod imp {
     use crate::config::{APP_ID, PROFILE};
 
+    use std::cell::Cell;
     use std::cell::RefCell;
     use std::sync::Arc;
     use std::sync::Mutex;
 
+    use gdk::gio::ApplicationHoldGuard;
+    use gdk::gio::SimpleAction;
     use gdk_pixbuf::glib::clone;
     use glib::subclass::InitializingObject;
     use gtk::glib;
@@ -107,13 +267,21 @@ pub mod imp {
     use tf_playlist::PlaylistManager;
 
     use crate::csv_file_manager::CsvFileManager;
+    use crate::gui::feed::feed_item_object::VideoObject;
     use crate::gui::feed::feed_page::FeedPage;
+    use crate::gui::feed::player_page::PlayerPage;
+    use crate::gui::feed::video_page::VideoPage;
     use crate::gui::filter::filter_page::FilterPage;
     use crate::gui::subscription::subscription_page::SubscriptionPage;
     use crate::gui::watch_later::WatchLaterPage;
+    use crate::provider::ProviderSubscriptionEvent;
+    use crate::provider::ProviderSubscriptionList;
 
     use super::setup_joiner;
 
+    /// An observer kept alive for as long as the widget lives.
+    type ObserverHandle<T> = RefCell<Option<Arc<Mutex<Box<dyn Observer<T> + Send>>>>>;
+
     #[derive(CompositeTemplate)]
     #[template(resource = "/ui/window.ui")]
     pub struct Window {
@@ -123,7 +291,18 @@ pub mod imp {
         #[template_child]
         pub(in crate::gui) application_stack_bar: TemplateChild<libadwaita::ViewSwitcherBar>,
 
+        #[template_child]
+        pub(super) toast_overlay: TemplateChild<libadwaita::ToastOverlay>,
+        #[template_child]
+        pub(super) leaflet: TemplateChild<libadwaita::Leaflet>,
+
         pub settings: gtk::gio::Settings,
+        /// The videos to play next, see [QueueWindow](crate::gui::feed::queue_window::QueueWindow).
+        pub(super) play_queue: gtk::gio::ListStore,
+        /// Keeps the application running while the window is hidden.
+        background_hold: RefCell<Option<ApplicationHoldGuard>>,
+        /// Whether the application should quit instead of running in the background.
+        quitting: Cell<bool>,
 
         #[template_child]
         pub(super) feed_page: TemplateChild<FeedPage>,
@@ -133,39 +312,182 @@ pub mod imp {
         pub(super) filter_page: TemplateChild<FilterPage>,
         #[template_child]
         pub(super) subscription_page: TemplateChild<SubscriptionPage>,
+        #[template_child]
+        pub(super) video_page: TemplateChild<VideoPage>,
+        #[template_child]
+        pub(super) player_page: TemplateChild<PlayerPage>,
 
         pub(in crate::gui) joiner: RefCell<Option<Joiner>>,
         playlist_manager: RefCell<Option<PlaylistManager<String, AnyVideo>>>,
         any_subscription_list: RefCell<Option<AnySubscriptionList>>,
-        _watchlater_file_manager:
-            RefCell<Option<Arc<Mutex<Box<dyn Observer<PlaylistEvent<AnyVideo>> + Send>>>>>,
-        _subscription_file_manager:
-            RefCell<Option<Arc<Mutex<Box<dyn Observer<SubscriptionEvent> + Send>>>>>,
-        _filter_file_manager:
-            RefCell<Option<Arc<Mutex<Box<dyn Observer<FilterEvent<AnyVideoFilter>> + Send>>>>>,
+        provider_subscription_list: RefCell<Option<ProviderSubscriptionList>>,
+        _watchlater_file_manager: ObserverHandle<PlaylistEvent<AnyVideo>>,
+        _subscription_file_manager: ObserverHandle<SubscriptionEvent>,
+        _provider_subscription_file_manager: ObserverHandle<ProviderSubscriptionEvent>,
+        _filter_file_manager: ObserverHandle<FilterEvent<AnyVideoFilter>>,
     }
 
     impl Default for Window {
         fn default() -> Self {
             Self {
                 settings: gtk::gio::Settings::new(APP_ID),
+                play_queue: gtk::gio::ListStore::new(VideoObject::static_type()),
                 application_stack: Default::default(),
                 application_stack_bar: Default::default(),
+                toast_overlay: Default::default(),
+                leaflet: Default::default(),
+                background_hold: Default::default(),
+                quitting: Default::default(),
                 feed_page: Default::default(),
                 watchlater_page: Default::default(),
                 filter_page: Default::default(),
                 subscription_page: Default::default(),
+                video_page: Default::default(),
+                player_page: Default::default(),
                 joiner: Default::default(),
                 playlist_manager: Default::default(),
                 any_subscription_list: Default::default(),
+                provider_subscription_list: Default::default(),
                 _watchlater_file_manager: Default::default(),
                 _subscription_file_manager: Default::default(),
+                _provider_subscription_file_manager: Default::default(),
                 _filter_file_manager: Default::default(),
             }
         }
     }
 
     impl Window {
+        /// Setup the actions of the application, e.g. used by notifications.
+        pub(super) fn setup_app_actions(&self, app: &gtk::Application) {
+            let obj = self.obj();
+
+            let action_show_feed = SimpleAction::new("show-feed", None);
+            action_show_feed.connect_activate(clone!(@weak obj => move |_, _| {
+                obj.imp().application_stack.set_visible_child(&obj.imp().feed_page.get());
+                obj.present();
+            }));
+
+            let action_open_video = SimpleAction::new("open-video", Some(glib::VariantTy::STRING));
+            action_open_video.connect_activate(clone!(@weak obj => move |_, url| {
+                if let Some(url) = url.and_then(|u| u.get::<String>()) {
+                    // Replace // with / because of the same bug as when copying to the clipboard.
+                    gtk::show_uri(Some(&obj), &url.replace("//watch", "/watch"), gdk::CURRENT_TIME);
+                }
+            }));
+
+            let action_play_video = SimpleAction::new("play-video", Some(glib::VariantTy::STRING));
+            action_play_video.connect_activate(clone!(@weak obj => move |_, url| {
+                if let Some(url) = url.and_then(|u| u.get::<String>()) {
+                    match obj.imp().feed_page.video(&url) {
+                        Some(video) => obj.play_video(&video),
+                        None => log::warn!("Video {} to play is not in the feed anymore", url),
+                    }
+                }
+            }));
+
+            let action_watch_later =
+                SimpleAction::new("watch-later", Some(glib::VariantTy::STRING));
+            action_watch_later.connect_activate(clone!(@weak obj => move |_, url| {
+                let video = url
+                    .and_then(|u| u.get::<String>())
+                    .and_then(|u| obj.imp().feed_page.video(&u))
+                    .and_then(|v| v.video());
+                let playlist_manager = obj.imp().playlist_manager.borrow().clone();
+                if let (Some(video), Some(mut playlist_manager)) = (video, playlist_manager) {
+                    if !playlist_manager.items(&"WATCHLATER".to_string()).contains(&video) {
+                        playlist_manager.toggle(&"WATCHLATER".to_string(), &video);
+                    }
+                }
+            }));
+
+            let action_quit = SimpleAction::new("quit", None);
+            action_quit.connect_activate(clone!(@weak obj => move |_, _| {
+                obj.imp().quitting.set(true);
+                obj.close();
+            }));
+
+            app.add_action(&action_show_feed);
+            app.add_action(&action_open_video);
+            app.add_action(&action_play_video);
+            app.add_action(&action_watch_later);
+            app.add_action(&action_quit);
+            app.set_accels_for_action("app.quit", &["<Control>q"]);
+
+            app.set_accels_for_action("win.reload", &["<Control>r", "F5"]);
+            app.set_accels_for_action("win.search", &["<Control>f"]);
+            app.set_accels_for_action("win.subscribe", &["<Control>n"]);
+            app.set_accels_for_action("win.back", &["<Alt>Left"]);
+            app.set_accels_for_action("win.show-help-overlay", &["<Control>question"]);
+            for page in 0..4 {
+                app.set_accels_for_action(
+                    &format!("win.show-page({})", page),
+                    &[&format!("<Alt>{}", page + 1)],
+                );
+            }
+        }
+
+        /// Setup the actions of the window, most of them having keyboard shortcuts.
+        fn setup_actions(&self) {
+            let obj = self.obj();
+
+            let builder = gtk::Builder::from_resource("/ui/help_overlay.ui");
+            let help_overlay: gtk::ShortcutsWindow = builder
+                .object("help_overlay")
+                .expect("help_overlay.ui to have at least one object help_overlay");
+            obj.set_help_overlay(Some(&help_overlay));
+
+            let action_reload = SimpleAction::new("reload", None);
+            action_reload.connect_activate(clone!(@weak obj => move |_, _| {
+                obj.imp().feed_page.reload();
+            }));
+
+            let action_search = SimpleAction::new("search", None);
+            action_search.connect_activate(clone!(@weak obj => move |_, _| {
+                let imp = obj.imp();
+                obj.hide_video();
+                let visible = imp.application_stack.visible_child();
+                if visible.as_ref() == Some(imp.watchlater_page.upcast_ref()) {
+                    imp.watchlater_page.toggle_search();
+                } else {
+                    imp.application_stack.set_visible_child(&imp.feed_page.get());
+                    imp.feed_page.toggle_search();
+                }
+            }));
+
+            let action_subscribe = SimpleAction::new("subscribe", None);
+            action_subscribe.connect_activate(clone!(@weak obj => move |_, _| {
+                let imp = obj.imp();
+                obj.hide_video();
+                imp.application_stack.set_visible_child(&imp.subscription_page.get());
+                imp.subscription_page.present_subscribe();
+            }));
+
+            let action_show_page = SimpleAction::new("show-page", Some(glib::VariantTy::INT32));
+            action_show_page.connect_activate(clone!(@weak obj => move |_, page| {
+                let imp = obj.imp();
+                let page: gtk::Widget = match page.and_then(|p| p.get::<i32>()) {
+                    Some(0) => imp.feed_page.get().upcast(),
+                    Some(1) => imp.watchlater_page.get().upcast(),
+                    Some(2) => imp.filter_page.get().upcast(),
+                    Some(3) => imp.subscription_page.get().upcast(),
+                    _ => return,
+                };
+                obj.hide_video();
+                imp.application_stack.set_visible_child(&page);
+            }));
+
+            let action_back = SimpleAction::new("back", None);
+            action_back.connect_activate(clone!(@weak obj => move |_, _| {
+                obj.hide_video();
+            }));
+
+            obj.add_action(&action_reload);
+            obj.add_action(&action_search);
+            obj.add_action(&action_subscribe);
+            obj.add_action(&action_show_page);
+            obj.add_action(&action_back);
+        }
+
         fn setup_feed(&self) {
             self.feed_page.connect_local(
                 "add-subscription",
@@ -207,7 +529,8 @@ pub mod imp {
                 .replace(Some(_watchlater_file_manager));
             self.watchlater_page
                 .get()
-                .set_playlist_manager(playlist_manager);
+                .set_playlist_manager(playlist_manager.clone());
+            self.video_page.get().set_playlist_manager(playlist_manager);
         }
 
         fn setup_subscriptions(&self) {
@@ -233,12 +556,31 @@ pub mod imp {
 
             subscription_list.attach(Arc::downgrade(&_subscription_file_manager));
 
+            let mut provider_subscription_list = ProviderSubscriptionList::default();
+
+            let mut provider_subscriptions_file_path = user_data_dir.clone();
+            provider_subscriptions_file_path.push("provider_subscriptions.csv");
+
+            let _provider_subscription_file_manager = Arc::new(Mutex::new(Box::new(
+                CsvFileManager::new(&provider_subscriptions_file_path, &mut |sub| {
+                    provider_subscription_list.add(sub)
+                }),
+            )
+                as Box<dyn Observer<ProviderSubscriptionEvent> + Send>));
+
+            provider_subscription_list.attach(Arc::downgrade(&_provider_subscription_file_manager));
+
             self.any_subscription_list
                 .replace(Some(subscription_list.clone()));
+            self.provider_subscription_list
+                .replace(Some(provider_subscription_list.clone()));
             self._subscription_file_manager
                 .replace(Some(_subscription_file_manager));
+            self._provider_subscription_file_manager
+                .replace(Some(_provider_subscription_file_manager));
             self.subscription_page.get().set_subscription_list(
                 subscription_list.clone(),
+                provider_subscription_list.clone(),
                 self.playlist_manager
                     .borrow()
                     .clone()
@@ -250,6 +592,7 @@ pub mod imp {
                     .clone()
                     .expect("PlaylistManager should be set up"),
                 joiner,
+                provider_subscription_list,
             );
 
             self.subscription_page.connect_local(
@@ -322,29 +665,67 @@ pub mod imp {
             self.setup_watch_later();
             self.setup_subscriptions();
             self.setup_filter();
+            self.setup_actions();
 
             let obj = self.obj();
             if PROFILE == "Devel" {
                 obj.add_css_class("devel");
             }
             obj.load_window_size();
+
+            // The details may be skipped when going back from the player, see `show_player`.
+            let video_page = self.video_page.get();
+            let player_page = self.player_page.get();
+            self.leaflet.connect_visible_child_notify(
+                clone!(@weak video_page, @weak player_page => move |leaflet| {
+                    if leaflet.visible_child().as_ref() != Some(player_page.upcast_ref()) {
+                        leaflet.page(&video_page).set_navigatable(true);
+                    }
+                }),
+            );
+            obj.connect_show(|obj| {
+                // Back from the background.
+                obj.imp().background_hold.take();
+            });
         }
     }
 
     impl WidgetImpl for Window {}
     impl WindowImpl for Window {
         fn close_request(&self) -> Inhibit {
-            let mut user_cache_dir = glib::user_cache_dir();
-            user_cache_dir.push("tubefeeder");
-
-            if user_cache_dir.exists() {
-                std::fs::remove_dir_all(user_cache_dir).unwrap_or(());
-            }
-
             let obj = self.obj();
             if let Err(err) = obj.save_window_size() {
                 log::warn!("Failed to save window state, {}", &err);
             }
+            self.feed_page.mark_seen();
+
+            if !self.quitting.get() && self.settings.boolean("run-in-background") {
+                if let Some(app) = obj.application() {
+                    log::debug!("Hiding the window, keep running in the background");
+                    self.background_hold.replace(Some(app.hold()));
+                    obj.set_visible(false);
+                    return Inhibit(true);
+                }
+            }
+
+            let mut user_cache_dir = glib::user_cache_dir();
+            user_cache_dir.push("tubefeeder");
+
+            // Keep the HTTP cache, such that it can be used the next time.
+            crate::http_cache::prune();
+            let http_cache_dir = crate::http_cache::cache_dir();
+            if let Ok(entries) = std::fs::read_dir(user_cache_dir) {
+                for path in entries.flatten().map(|e| e.path()) {
+                    if path == http_cache_dir {
+                        continue;
+                    }
+                    if path.is_dir() {
+                        std::fs::remove_dir_all(path).unwrap_or(());
+                    } else {
+                        std::fs::remove_file(path).unwrap_or(());
+                    }
+                }
+            }
 
             self.parent_close_request()
         }
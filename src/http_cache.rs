@@ -0,0 +1,290 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A on-disk cache for HTTP responses.
+//!
+//! Responses are stored together with their `ETag` and `Last-Modified` headers. Responses that
+//! are still fresh according to their `Cache-Control` header are served from the disk directly,
+//! otherwise they are revalidated using `If-None-Match` and `If-Modified-Since`. A `304 Not
+//! Modified` response means the cached response did not change.
+//!
+//! The cache is kept when closing the application, unlike e.g. the thumbnails. To keep it from
+//! growing without bound, [prune] removes responses that were not stored or revalidated for a
+//! while and the oldest responses exceeding a total size.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use once_cell::sync::Lazy;
+use reqwest::{
+    header::{
+        HeaderMap, HeaderValue, CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        LAST_MODIFIED,
+    },
+    Client, RequestBuilder, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+static STATISTICS: Lazy<Mutex<Statistics>> = Lazy::new(Default::default);
+
+/// Statistics about the usage of the cache since the start of the application.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Statistics {
+    /// The number of requests made through the cache.
+    pub requests: u64,
+    /// The number of requests answered from the cache, without revalidating them.
+    pub fresh: u64,
+    /// The number of requests revalidated with a `304 Not Modified` response.
+    pub not_modified: u64,
+    /// The number of bytes downloaded.
+    pub downloaded: u64,
+    /// The number of bytes served from the cache instead of downloading them.
+    pub saved: u64,
+}
+
+/// The statistics about the usage of the cache since the start of the application.
+pub fn statistics() -> Statistics {
+    *STATISTICS.lock().expect("Statistics to be lockable")
+}
+
+fn update_statistics<F: FnOnce(&mut Statistics)>(f: F) {
+    f(&mut STATISTICS.lock().expect("Statistics to be lockable"))
+}
+
+/// The directory the responses are cached in.
+pub fn cache_dir() -> PathBuf {
+    let mut path = gtk::glib::user_cache_dir();
+    path.push("tubefeeder");
+    path.push("http");
+    path
+}
+
+/// Remove all cached responses.
+pub fn clear() {
+    let path = cache_dir();
+    if path.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&path) {
+            log::error!("Failed to clear the HTTP cache at {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Responses not stored or revalidated for this long are removed by [prune].
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+/// The maximal size of all cached responses kept by [prune], in bytes.
+const MAX_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Remove cached responses older than [MAX_AGE] and the oldest ones exceeding [MAX_SIZE].
+pub fn prune() {
+    let Ok(dir) = std::fs::read_dir(cache_dir()) else {
+        return;
+    };
+
+    // The metadata and body of a response share the same key and are removed together.
+    let mut keys: HashMap<String, (SystemTime, u64, Vec<PathBuf>)> = HashMap::new();
+    for file in dir.flatten() {
+        let Ok(metadata) = file.metadata() else {
+            continue;
+        };
+        let name = file.file_name().to_string_lossy().into_owned();
+        let key = name.trim_end_matches(".json").to_owned();
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+
+        let (last_modified, size, paths) =
+            keys.entry(key)
+                .or_insert((SystemTime::UNIX_EPOCH, 0, vec![]));
+        *last_modified = (*last_modified).max(modified);
+        *size += metadata.len();
+        paths.push(file.path());
+    }
+
+    let mut keys: Vec<_> = keys.into_values().collect();
+    keys.sort_by_key(|k| std::cmp::Reverse(k.0));
+
+    let now = SystemTime::now();
+    let mut total = 0;
+    for (modified, size, paths) in keys {
+        total += size;
+        let expired = now.duration_since(modified).unwrap_or_default() > MAX_AGE;
+        if expired || total > MAX_SIZE {
+            for path in paths {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    log::warn!("Failed to remove the cached response {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+}
+
+/// The metadata of a cached response. The body is stored in a separate file.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Entry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Until when the response is fresh, as unix timestamp in seconds.
+    fresh_until: Option<i64>,
+}
+
+impl Entry {
+    fn is_fresh(&self) -> bool {
+        self.fresh_until
+            .map(|t| t > chrono::Utc::now().timestamp())
+            .unwrap_or_default()
+    }
+
+    fn is_validatable(&self) -> bool {
+        self.etag.is_some() || self.last_modified.is_some() || self.fresh_until.is_some()
+    }
+
+    /// Update the validators and freshness from the headers of a response.
+    ///
+    /// Returns `false` if the response must not be stored.
+    fn update(&mut self, headers: &HeaderMap) -> bool {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(|v| v.to_owned())
+        };
+        if let Some(etag) = header(ETAG) {
+            self.etag = Some(etag);
+        }
+        if let Some(last_modified) = header(LAST_MODIFIED) {
+            self.last_modified = Some(last_modified);
+        }
+        self.fresh_until = None;
+
+        let cache_control = header(CACHE_CONTROL).unwrap_or_default().to_lowercase();
+        for directive in cache_control.split(',').map(|d| d.trim()) {
+            if directive == "no-store" {
+                return false;
+            } else if directive == "no-cache" {
+                self.fresh_until = None;
+                break;
+            } else if let Some(max_age) = directive
+                .strip_prefix("max-age=")
+                .and_then(|a| a.parse::<i64>().ok())
+            {
+                self.fresh_until = Some(chrono::Utc::now().timestamp() + max_age);
+            }
+        }
+        self.is_validatable()
+    }
+}
+
+/// The paths of the metadata and body of the cached response of the given url.
+fn paths(url: &str) -> (PathBuf, PathBuf) {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    let key = format!("{:016x}", hasher.finish());
+
+    let dir = cache_dir();
+    (dir.join(format!("{}.json", key)), dir.join(key))
+}
+
+async fn load(url: &str) -> Option<(Entry, Vec<u8>)> {
+    let (entry_path, body_path) = paths(url);
+    let entry: Entry = serde_json::from_slice(&tokio::fs::read(entry_path).await.ok()?).ok()?;
+    // Different urls may have the same hash.
+    if entry.url != url {
+        return None;
+    }
+    let body = tokio::fs::read(body_path).await.ok()?;
+    Some((entry, body))
+}
+
+async fn store(entry: &Entry, body: Option<&[u8]>) {
+    let (entry_path, body_path) = paths(&entry.url);
+    let result = async {
+        tokio::fs::create_dir_all(cache_dir()).await?;
+        if let Some(body) = body {
+            tokio::fs::write(body_path, body).await?;
+        }
+        tokio::fs::write(entry_path, serde_json::to_vec(entry)?).await
+    }
+    .await;
+    if let Err(e) = result {
+        log::warn!("Failed to cache the response of {}: {}", entry.url, e);
+    }
+}
+
+/// Get the body of the response of the given url, using the cache if possible.
+pub async fn get<S: AsRef<str>>(client: &Client, url: S) -> Result<Vec<u8>, reqwest::Error> {
+    send(client, client.get(url.as_ref())).await
+}
+
+/// Send the given `GET` request and get the body of the response, using the cache if possible.
+pub async fn send(client: &Client, request: RequestBuilder) -> Result<Vec<u8>, reqwest::Error> {
+    let mut request = request.build()?;
+    let url = request.url().to_string();
+    update_statistics(|s| s.requests += 1);
+
+    let cached = load(&url).await;
+    if let Some((entry, body)) = &cached {
+        if entry.is_fresh() {
+            log::trace!("Using the cached response of {}", url);
+            update_statistics(|s| {
+                s.fresh += 1;
+                s.saved += body.len() as u64;
+            });
+            return Ok(body.clone());
+        }
+        let headers = request.headers_mut();
+        if let Some(etag) = entry.etag.as_ref().and_then(|e| e.parse().ok()) {
+            headers.insert(IF_NONE_MATCH, etag);
+        }
+        if let Some(date) = entry.last_modified.as_ref().and_then(|d| d.parse().ok()) {
+            headers.insert(IF_MODIFIED_SINCE, date);
+        }
+    }
+
+    let response = client.execute(request).await?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some((mut entry, body)) = cached {
+            log::trace!("The cached response of {} was not modified", url);
+            update_statistics(|s| {
+                s.not_modified += 1;
+                s.saved += body.len() as u64;
+            });
+            if entry.update(response.headers()) {
+                store(&entry, None).await;
+            }
+            return Ok(body);
+        }
+    }
+
+    let response = response.error_for_status()?;
+    let mut entry = Entry {
+        url,
+        ..Default::default()
+    };
+    let cacheable = entry.update(response.headers());
+    let body = response.bytes().await?.to_vec();
+    update_statistics(|s| s.downloaded += body.len() as u64);
+    if cacheable {
+        store(&entry, Some(&body)).await;
+    }
+    Ok(body)
+}
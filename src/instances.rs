@@ -0,0 +1,193 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The instances of the [Backend]s.
+//!
+//! The configured instances are a prioritized list, stored space-separated in the
+//! `PIPED_INSTANCES` and `INVIDIOUS_INSTANCES` environment variables. `PIPED_API_URL` and
+//! `INVIDIOUS_API_URL` can be used to put a instance in front of that list.
+//!
+//! Instances failing a request are considered unhealthy for a while and will only be tried after
+//! all healthy instances.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use crate::youtube::Backend;
+
+const PIPED_INSTANCE_LIST: &str = "https://piped-instances.kavin.rocks/";
+const INVIDIOUS_INSTANCE_LIST: &str = "https://api.invidious.io/instances.json?sort_by=health";
+
+/// How long a instance is considered unhealthy after it failed.
+const UNHEALTHY_DURATION: Duration = Duration::from_secs(5 * 60);
+/// The timeout of a health check.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+static UNHEALTHY: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(Default::default);
+
+impl Backend {
+    fn instances_env(&self) -> &'static str {
+        match self {
+            Backend::Piped => "PIPED_INSTANCES",
+            Backend::Invidious => "INVIDIOUS_INSTANCES",
+        }
+    }
+
+    fn api_url_env(&self) -> &'static str {
+        match self {
+            Backend::Piped => "PIPED_API_URL",
+            Backend::Invidious => "INVIDIOUS_API_URL",
+        }
+    }
+
+    /// The GSettings-key storing the instances of the backend.
+    pub fn instances_setting(&self) -> &'static str {
+        match self {
+            Backend::Piped => "piped-instances",
+            Backend::Invidious => "invidious-instances",
+        }
+    }
+}
+
+/// The configured instances of the backend, by priority.
+pub fn configured(backend: Backend) -> Vec<String> {
+    let mut instances: Vec<String> = crate::environment::var(backend.api_url_env())
+        .into_iter()
+        .collect();
+    if let Some(list) = crate::environment::var(backend.instances_env()) {
+        for instance in list.split_whitespace() {
+            if !instances.iter().any(|i| i == instance) {
+                instances.push(instance.to_owned());
+            }
+        }
+    }
+    instances
+}
+
+/// Set the configured instances of the backend.
+pub fn set_configured(backend: Backend, instances: &[String]) {
+    crate::environment::set_var(backend.instances_env(), instances.join(" "));
+}
+
+/// The configured instances of the backend in the order they should be tried.
+///
+/// Unhealthy instances are tried last.
+pub fn ordered(backend: Backend) -> Vec<String> {
+    let mut unhealthy = UNHEALTHY
+        .lock()
+        .expect("Unhealthy instances to be lockable");
+    unhealthy.retain(|_, since| since.elapsed() < UNHEALTHY_DURATION);
+    let (healthy, failed): (Vec<String>, Vec<String>) = configured(backend)
+        .into_iter()
+        .partition(|i| !unhealthy.contains_key(i));
+    healthy.into_iter().chain(failed).collect()
+}
+
+/// Mark the instance as unhealthy because a request to it failed.
+pub fn report_failure(instance: &str) {
+    log::warn!("Instance {} failed, marking it as unhealthy", instance);
+    UNHEALTHY
+        .lock()
+        .expect("Unhealthy instances to be lockable")
+        .insert(instance.to_owned(), Instant::now());
+}
+
+/// Mark the instance as healthy because a request to it succeeded.
+pub fn report_success(instance: &str) {
+    UNHEALTHY
+        .lock()
+        .expect("Unhealthy instances to be lockable")
+        .remove(instance);
+}
+
+/// Check whether the instance is reachable and measure the latency.
+///
+/// The health of the instance will be updated accordingly.
+pub async fn check(backend: Backend, instance: &str) -> Option<Duration> {
+    let path = match backend {
+        Backend::Piped => "healthcheck",
+        Backend::Invidious => "api/v1/stats",
+    };
+    let url = format!("{}/{}", instance.trim_end_matches('/'), path);
+    let client = reqwest::Client::new();
+    let start = Instant::now();
+    let result = client.get(&url).timeout(CHECK_TIMEOUT).send().await;
+    match result.and_then(|r| r.error_for_status()) {
+        Ok(_) => {
+            report_success(instance);
+            Some(start.elapsed())
+        }
+        Err(e) => {
+            log::debug!("Health check of {} failed: {}", instance, e);
+            report_failure(instance);
+            None
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PipedInstance {
+    api_url: String,
+}
+
+#[derive(Deserialize)]
+struct InvidiousInstance {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    api: Option<bool>,
+    uri: String,
+}
+
+/// Load the list of public instances of the backend.
+pub async fn public(backend: Backend) -> crate::api::Result<Vec<String>> {
+    let client = reqwest::Client::new();
+    let list_url = match backend {
+        Backend::Piped => PIPED_INSTANCE_LIST,
+        Backend::Invidious => INVIDIOUS_INSTANCE_LIST,
+    };
+    let response = client
+        .get(list_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    match backend {
+        Backend::Piped => {
+            let instances: Vec<PipedInstance> = serde_json::from_slice(&response)?;
+            Ok(instances.into_iter().map(|i| i.api_url).collect())
+        }
+        Backend::Invidious => {
+            let instances: Vec<(String, InvidiousInstance)> = serde_json::from_slice(&response)?;
+            Ok(instances
+                .into_iter()
+                .map(|(_, i)| i)
+                .filter(|i| i.kind == "https" && i.api == Some(true))
+                .map(|i| i.uri)
+                .collect())
+        }
+    }
+}
@@ -0,0 +1,144 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A minimal client for the [Invidious](https://docs.invidious.io/api/) API.
+
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::api::Result;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Thumbnail {
+    pub quality: String,
+    pub url: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelSearchItem {
+    pub author: String,
+    pub author_id: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelVideo {
+    pub title: String,
+    pub video_id: String,
+    pub author: String,
+    pub author_id: String,
+    #[serde(default)]
+    pub video_thumbnails: Vec<Thumbnail>,
+    /// The upload time as unix timestamp in seconds.
+    pub published: i64,
+    #[serde(default)]
+    pub length_seconds: i64,
+    #[serde(default)]
+    pub view_count: i64,
+    #[serde(default)]
+    pub live_now: bool,
+    #[serde(default)]
+    pub is_upcoming: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoInfo {
+    #[serde(default)]
+    pub description: String,
+    /// The upload time as unix timestamp in seconds.
+    #[serde(default)]
+    pub published: i64,
+    #[serde(default)]
+    pub view_count: i64,
+    #[serde(default)]
+    pub like_count: i64,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+/// Newer instances wrap the videos of a channel into a object, older ones return the plain list.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChannelVideos {
+    Wrapped { videos: Vec<ChannelVideo> },
+    Plain(Vec<ChannelVideo>),
+}
+
+pub struct InvidiousClient {
+    client: Client,
+    instance: String,
+}
+
+impl InvidiousClient {
+    pub fn new<S: AsRef<str>>(client: &Client, instance: S) -> Self {
+        Self {
+            client: client.clone(),
+            instance: instance.as_ref().trim_end_matches('/').to_owned(),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> Result<T> {
+        let request = self
+            .client
+            .get(format!("{}/api/v1/{}", self.instance, path))
+            .query(query);
+        let response = crate::http_cache::send(&self.client, request).await?;
+        Ok(serde_json::from_slice(&response)?)
+    }
+
+    /// Search for channels matching the given name.
+    pub async fn search_channel<S: AsRef<str>>(&self, name: S) -> Result<Vec<ChannelSearchItem>> {
+        self.get("search", &[("q", name.as_ref()), ("type", "channel")])
+            .await
+    }
+
+    /// The latest videos of the channel with the given id.
+    pub async fn channel_videos<S: AsRef<str>>(&self, id: S) -> Result<Vec<ChannelVideo>> {
+        let videos: ChannelVideos = self
+            .get(&format!("channels/{}/videos", id.as_ref()), &[])
+            .await?;
+        Ok(match videos {
+            ChannelVideos::Wrapped { videos } => videos,
+            ChannelVideos::Plain(videos) => videos,
+        })
+    }
+
+    /// The metadata of the video with the given id.
+    pub async fn video_from_id<S: AsRef<str>>(&self, id: S) -> Result<VideoInfo> {
+        self.get(&format!("videos/{}", id.as_ref()), &[]).await
+    }
+
+    /// The url of the best thumbnail in the list.
+    ///
+    /// Some instances return urls relative to the instance.
+    pub fn thumbnail_url(&self, thumbnails: &[Thumbnail]) -> String {
+        let thumbnail = thumbnails
+            .iter()
+            .find(|t| t.quality == "high")
+            .or_else(|| thumbnails.first());
+        match thumbnail {
+            Some(t) if t.url.starts_with('/') => format!("{}{}", self.instance, t.url),
+            Some(t) => t.url.clone(),
+            None => String::new(),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Details of LBRY videos, loaded from the API of Odysee.
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::details::VideoDetails;
+
+const API_URL: &str = "https://api.na-backend.odysee.com/api/v1/proxy";
+
+#[derive(Deserialize)]
+struct Response {
+    result: std::collections::HashMap<String, Claim>,
+}
+
+#[derive(Deserialize)]
+struct Claim {
+    value: ClaimValue,
+}
+
+#[derive(Deserialize)]
+struct ClaimValue {
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// The release time as unix timestamp in seconds.
+    #[serde(default)]
+    release_time: Option<String>,
+}
+
+/// The LBRY url of the video with the given Odysee url.
+fn lbry_url(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    Some(format!("lbry:/{}", url.path()))
+}
+
+/// Get the details of the video with the given url.
+pub async fn video_details(url: &str) -> Option<VideoDetails> {
+    let lbry_url = lbry_url(url)?;
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": "resolve",
+        "params": { "urls": [lbry_url] },
+    });
+    let response = reqwest::Client::new()
+        .post(API_URL)
+        .query(&[("m", "resolve")])
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(request.to_string())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status());
+    let body = match response {
+        Ok(r) => r.bytes().await,
+        Err(e) => Err(e),
+    };
+    let response: Response = body
+        .map_err(|e| e.to_string())
+        .and_then(|b| serde_json::from_slice(&b).map_err(|e| e.to_string()))
+        .map_err(|e| log::error!("Failed to get the details of {}: {}", url, e))
+        .ok()?;
+    let claim = response.result.into_values().next()?.value;
+
+    Some(VideoDetails {
+        description: claim.description,
+        tags: claim.tags,
+        chapters: vec![],
+        uploaded: claim
+            .release_time
+            .and_then(|t| t.parse().ok())
+            .and_then(|t| chrono::NaiveDateTime::from_timestamp_opt(t, 0)),
+        views: None,
+        likes: None,
+    })
+}
@@ -22,29 +22,158 @@ use gdk::{
     prelude::{ApplicationExt, ApplicationExtManual},
     Display,
 };
-use gdk_pixbuf::{gio::Settings, prelude::SettingsExt};
-use gtk::{traits::GtkWindowExt, CssProvider};
+use gdk_pixbuf::{
+    gio::Settings,
+    prelude::{SettingsExt, SettingsExtManual},
+};
+use gtk::{
+    glib::{clone, Continue, MainContext, PRIORITY_DEFAULT},
+    traits::{GtkApplicationExt, GtkWindowExt},
+    CssProvider,
+};
 
+mod api;
+mod command;
+mod comments;
 mod config;
 use self::config::{APP_ID, GETTEXT_PACKAGE, LOCALEDIR, RESOURCES_BYTES};
 
 mod csv_file_manager;
+mod details;
 mod downloader;
+mod environment;
+mod generator;
 mod gui;
+mod http_cache;
 mod import;
+mod instances;
+mod invidious;
+mod lbry;
+mod media;
+mod metadata;
+mod mpv;
+mod peertube;
 mod player;
+mod presets;
+mod profiles;
+mod provider;
+mod rss;
+mod scheduler;
+mod search;
+mod watched;
+mod youtube;
 
 fn init_setting(env: &'static str, value: &str) {
-    if std::env::var_os(env).is_none() {
-        std::env::set_var(env, value);
+    if environment::var(env).is_none() {
+        environment::set_var(env, value);
     }
 }
 
+fn strv_setting(settings: &Settings, key: &str) -> String {
+    settings
+        .strv(key)
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Replace the default commands of the player and downloader by the first installed presets if
+/// the programs of the defaults are not installed.
+///
+/// This is only done once, as checking the programs may take a while on Flatpak. The programs are
+/// checked in the background to not delay the start.
+fn select_installed(settings: &Settings) {
+    if settings.boolean("installed-selected") {
+        return;
+    }
+    let defaults: Vec<(&'static str, &'static str, String, Vec<presets::Preset>)> = [
+        ("player", "PLAYER", presets::players()),
+        ("downloader", "DOWNLOADER", presets::downloaders()),
+    ]
+    .into_iter()
+    .filter(|(key, _, _)| settings.user_value(key).is_none())
+    .map(|(key, env, presets)| (key, env, settings.string(key).to_string(), presets))
+    .collect();
+
+    let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+    std::thread::spawn(move || {
+        let selected: Vec<(&str, &str, presets::Preset)> = defaults
+            .into_iter()
+            .filter(|(_, _, command, _)| !presets::command_installed(command))
+            .filter_map(|(key, env, _, presets)| {
+                presets
+                    .into_iter()
+                    .find(presets::Preset::installed)
+                    .map(|preset| (key, env, preset))
+            })
+            .collect();
+        let _ = sender.send(selected);
+    });
+    receiver.attach(
+        None,
+        clone!(@strong settings => move |selected| {
+            for (key, env, preset) in selected {
+                log::info!("Using the installed {} for {}", preset.name, key);
+                let _ = settings.set_string(key, &preset.command);
+                // The environment of the process takes precedence over the settings.
+                if std::env::var_os(env).is_none() {
+                    environment::set_var(env, preset.command);
+                }
+            }
+            let _ = settings.set_boolean("installed-selected", true);
+            Continue(false)
+        }),
+    );
+}
+
 fn init_settings() {
     let settings = Settings::new(APP_ID);
+    select_installed(&settings);
     init_setting("PLAYER", &settings.string("player"));
+    if environment::var("PLAYER_PROFILES").is_none() {
+        let profiles: Vec<profiles::Profile> = settings
+            .get::<Vec<profiles::ProfileTuple>>("player-profiles")
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        profiles::set_configured(&profiles);
+    }
+    init_setting("PLAYER_PROFILE", &settings.string("player-profile"));
     init_setting("DOWNLOADER", &settings.string("downloader"));
-    init_setting("PIPED_API_URL", &settings.string("piped-url"));
+    init_setting("MPV_IPC", &settings.boolean("mpv-ipc").to_string());
+    init_setting("YOUTUBE_BACKEND", &settings.string("youtube-backend"));
+    init_setting(
+        "FETCH_CONCURRENCY",
+        &settings.uint("fetch-concurrency").to_string(),
+    );
+    init_setting("FETCH_TIMEOUT", &settings.uint("fetch-timeout").to_string());
+    init_setting("FETCH_RETRIES", &settings.uint("fetch-retries").to_string());
+    init_setting(
+        "FETCH_HOST_INTERVAL",
+        &settings.uint("fetch-host-interval").to_string(),
+    );
+
+    // Migrate the single piped url into the list of instances.
+    if settings.user_value("piped-instances").is_none()
+        && settings.user_value("piped-url").is_some()
+    {
+        let piped_url = settings.string("piped-url");
+        let _ = settings.set_strv("piped-instances", [piped_url.as_str()].as_slice());
+    }
+    init_setting(
+        "PIPED_INSTANCES",
+        &strv_setting(&settings, "piped-instances"),
+    );
+    init_setting(
+        "INVIDIOUS_INSTANCES",
+        &strv_setting(&settings, "invidious-instances"),
+    );
+
+    // Migrate only showing the videos of yesterday into the date range.
+    if settings.user_value("date-range").is_none() && settings.boolean("only-videos-yesterday") {
+        let _ = settings.set_string("date-range", "yesterday");
+    }
 }
 
 fn init_resources() {
@@ -68,6 +197,12 @@ fn init_folders() {
     if !user_data_dir.exists() {
         std::fs::create_dir_all(user_data_dir.clone()).expect("could not create user data dir");
     }
+
+    let providers_dir = crate::provider::providers_dir();
+
+    if !providers_dir.exists() {
+        std::fs::create_dir_all(&providers_dir).expect("could not create providers dir");
+    }
 }
 
 fn init_internationalization() -> Result<(), Box<dyn std::error::Error>> {
@@ -103,6 +238,11 @@ async fn main() {
 }
 
 fn build_ui(app: &gtk::Application) {
+    // The window may be hidden while running in the background.
+    if let Some(window) = app.windows().first() {
+        window.present();
+        return;
+    }
     init_resources();
     init_folders();
     init_settings();
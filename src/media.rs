@@ -0,0 +1,61 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The media which can be played in the application.
+//!
+//! These are downloaded videos, links directly to media files and the files of PeerTube videos.
+
+use std::path::Path;
+
+use tf_join::Platform;
+
+/// The extensions of files the built-in player can play, including HLS playlists.
+const MEDIA_EXTENSIONS: [&str; 7] = ["mp4", "webm", "mkv", "mov", "ogv", "ogg", "m3u8"];
+
+/// Whether the url links to a media file instead of a website.
+pub fn is_direct(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.path_segments()?.next_back().map(str::to_lowercase))
+        .and_then(|file| file.rsplit_once('.').map(|(_, e)| e.to_owned()))
+        .map(|extension| MEDIA_EXTENSIONS.contains(&extension.as_str()))
+        .unwrap_or_default()
+}
+
+/// The uri of the media of the video, if it can be played in the application.
+///
+/// The downloaded file is preferred over streaming the video.
+pub async fn uri(
+    url: String,
+    platform: Option<String>,
+    local_path: Option<String>,
+) -> Option<String> {
+    if let Some(path) = local_path.filter(|p| Path::new(p).exists()) {
+        return gtk::glib::filename_to_uri(path, None)
+            .ok()
+            .map(|u| u.to_string());
+    }
+    if is_direct(&url) {
+        return Some(url);
+    }
+    if platform == Some(Platform::Peertube.to_string()) {
+        return crate::peertube::media_url(&url).await;
+    }
+    None
+}
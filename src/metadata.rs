@@ -0,0 +1,96 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Additional metadata of videos.
+//!
+//! The videos of the platforms (e.g. [tf_yt::YTVideo]) only contain the title, url, thumbnail and
+//! upload time. Additional metadata known when fetching the videos is stored here, by the url of
+//! the video, until the objects showing the videos take it over. Metadata of videos no longer in
+//! the feed is removed after each reload with [retain].
+
+use std::{collections::HashMap, sync::Mutex};
+
+use once_cell::sync::Lazy;
+
+static METADATA: Lazy<Mutex<HashMap<String, VideoMetadata>>> = Lazy::new(Default::default);
+
+/// What kind of content a video is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum VideoKind {
+    #[default]
+    Video,
+    Live,
+    Upcoming,
+    Short,
+}
+
+impl VideoKind {
+    /// Whether the video is a livestream, either running or upcoming.
+    pub fn is_livestream(&self) -> bool {
+        matches!(self, VideoKind::Live | VideoKind::Upcoming)
+    }
+}
+
+impl std::str::FromStr for VideoKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "video" => Ok(VideoKind::Video),
+            "live" => Ok(VideoKind::Live),
+            "upcoming" => Ok(VideoKind::Upcoming),
+            "short" => Ok(VideoKind::Short),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The additional metadata of a video.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct VideoMetadata {
+    /// The duration in seconds.
+    pub duration: Option<i64>,
+    pub views: Option<i64>,
+    pub kind: VideoKind,
+}
+
+/// Store the metadata of the video with the given url.
+pub fn insert<S: AsRef<str>>(url: S, metadata: VideoMetadata) {
+    METADATA
+        .lock()
+        .expect("Metadata to be lockable")
+        .insert(url.as_ref().to_owned(), metadata);
+}
+
+/// The metadata of the video with the given url, if known.
+pub fn get<S: AsRef<str>>(url: S) -> Option<VideoMetadata> {
+    METADATA
+        .lock()
+        .expect("Metadata to be lockable")
+        .get(url.as_ref())
+        .cloned()
+}
+
+/// Only keep the metadata of the videos with urls for which `keep` returns `true`.
+pub fn retain<F: FnMut(&str) -> bool>(mut keep: F) {
+    METADATA
+        .lock()
+        .expect("Metadata to be lockable")
+        .retain(|url, _| keep(url));
+}
@@ -0,0 +1,222 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Tracking the playback of mpv using its [JSON IPC](https://mpv.io/manual/stable/#json-ipc).
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// mpv creates the socket shortly after starting, try connecting for a while.
+const CONNECT_ATTEMPTS: usize = 50;
+const CONNECT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The observed properties, their observation id is their index plus one.
+const PROPERTIES: [&str; 4] = ["time-pos", "duration", "pause", "playlist-pos"];
+
+/// A change of the playback reported by mpv.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// The current position in seconds.
+    Position(f64),
+    /// The duration of the video in seconds.
+    Duration(f64),
+    Pause(bool),
+    /// The index of the playing entry of the playlist.
+    PlaylistPosition(usize),
+    /// The video was played until the end.
+    EndOfFile,
+}
+
+/// Whether the program is mpv and tracking it is enabled.
+pub fn enabled(program: &str) -> bool {
+    let enabled = crate::environment::var("MPV_IPC")
+        .map(|v| v != "false")
+        .unwrap_or(true);
+    enabled && Path::new(program).file_name() == Some("mpv".as_ref())
+}
+
+/// A new path for the socket of a mpv instance, unique in this session.
+pub fn socket_path() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let mut path = gtk::glib::user_runtime_dir();
+    path.push(format!(
+        "tubefeeder-mpv-{}-{}.sock",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    path
+}
+
+/// The argument making mpv listen on the socket.
+pub fn argument(socket: &Path) -> String {
+    format!("--input-ipc-server={}", socket.display())
+}
+
+/// Parse a line sent by mpv, ignoring everything not being an [Event].
+pub fn parse_event(line: &str) -> Option<Event> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    match value.get("event")?.as_str()? {
+        "property-change" => {
+            let data = value.get("data")?;
+            match value.get("name")?.as_str()? {
+                "time-pos" => data.as_f64().map(Event::Position),
+                "duration" => data.as_f64().map(Event::Duration),
+                "pause" => data.as_bool().map(Event::Pause),
+                // mpv reports -1 if no entry is playing.
+                "playlist-pos" => data.as_u64().map(|p| Event::PlaylistPosition(p as usize)),
+                _ => None,
+            }
+        }
+        "end-file" if value.get("reason").and_then(|r| r.as_str()) == Some("eof") => {
+            Some(Event::EndOfFile)
+        }
+        _ => None,
+    }
+}
+
+/// Connect to mpv listening on the socket and call `callback` with every [Event] until mpv quits.
+pub fn watch<F: FnMut(Event)>(socket: &Path, mut callback: F) -> std::io::Result<()> {
+    let stream = connect(socket)?;
+    let mut writer = stream.try_clone()?;
+    for (id, property) in PROPERTIES.iter().enumerate() {
+        let command = serde_json::json!({ "command": ["observe_property", id + 1, property] });
+        writeln!(writer, "{}", command)?;
+    }
+    for line in BufReader::new(stream).lines() {
+        if let Some(event) = parse_event(&line?) {
+            callback(event);
+        }
+    }
+    Ok(())
+}
+
+fn connect(socket: &Path) -> std::io::Result<UnixStream> {
+    let mut attempt = 1;
+    loop {
+        match UnixStream::connect(socket) {
+            Ok(stream) => return Ok(stream),
+            Err(e) if attempt >= CONNECT_ATTEMPTS => return Err(e),
+            Err(_) => {
+                attempt += 1;
+                std::thread::sleep(CONNECT_INTERVAL);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixListener;
+
+    use super::*;
+
+    #[test]
+    fn parse_property_changes() {
+        assert_eq!(
+            parse_event(r#"{"event":"property-change","id":1,"name":"time-pos","data":12.5}"#),
+            Some(Event::Position(12.5))
+        );
+        assert_eq!(
+            parse_event(r#"{"event":"property-change","id":2,"name":"duration","data":600}"#),
+            Some(Event::Duration(600.0))
+        );
+        assert_eq!(
+            parse_event(r#"{"event":"property-change","id":3,"name":"pause","data":true}"#),
+            Some(Event::Pause(true))
+        );
+        assert_eq!(
+            parse_event(r#"{"event":"property-change","id":4,"name":"playlist-pos","data":2}"#),
+            Some(Event::PlaylistPosition(2))
+        );
+        assert_eq!(
+            parse_event(r#"{"event":"property-change","id":4,"name":"playlist-pos","data":-1}"#),
+            None
+        );
+        // The position is unavailable before playback starts.
+        assert_eq!(
+            parse_event(r#"{"event":"property-change","id":1,"name":"time-pos"}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_end_of_file() {
+        assert_eq!(
+            parse_event(r#"{"event":"end-file","reason":"eof","playlist_entry_id":1}"#),
+            Some(Event::EndOfFile)
+        );
+        assert_eq!(
+            parse_event(r#"{"event":"end-file","reason":"quit","playlist_entry_id":1}"#),
+            None
+        );
+        assert_eq!(parse_event(r#"{"data":null,"error":"success"}"#), None);
+        assert_eq!(parse_event("not json"), None);
+    }
+
+    #[test]
+    fn watch_fake_server() {
+        let socket =
+            std::env::temp_dir().join(format!("tubefeeder-mpv-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).expect("Socket to be bindable");
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("Client to connect");
+            let mut reader = BufReader::new(stream.try_clone().expect("Stream to be cloneable"));
+            let mut commands = Vec::new();
+            for _ in PROPERTIES {
+                let mut line = String::new();
+                reader.read_line(&mut line).expect("Command to be readable");
+                commands.push(line);
+            }
+            let mut writer = stream;
+            for line in [
+                r#"{"data":null,"request_id":0,"error":"success"}"#,
+                r#"{"event":"property-change","id":2,"name":"duration","data":100.0}"#,
+                r#"{"event":"property-change","id":1,"name":"time-pos","data":1.5}"#,
+                r#"{"event":"property-change","id":3,"name":"pause","data":true}"#,
+                r#"{"event":"end-file","reason":"eof"}"#,
+            ] {
+                writeln!(writer, "{}", line).expect("Event to be writable");
+            }
+            commands
+        });
+
+        let mut events = Vec::new();
+        watch(&socket, |e| events.push(e)).expect("Watching to succeed");
+        let commands = server.join().expect("Server to succeed");
+        let _ = std::fs::remove_file(&socket);
+
+        assert!(commands[0].contains(r#"["observe_property",1,"time-pos"]"#));
+        assert!(commands[2].contains(r#"["observe_property",3,"pause"]"#));
+        assert_eq!(
+            events,
+            vec![
+                Event::Duration(100.0),
+                Event::Position(1.5),
+                Event::Pause(true),
+                Event::EndOfFile
+            ]
+        );
+    }
+}
@@ -0,0 +1,280 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Videos of PeerTube channels with their metadata.
+//!
+//! The videos are taken from the API of the instance, as the RSS feed of the channel (see
+//! [tf_pt]) does not contain e.g. the duration. The RSS feed is only used if the API fails.
+//!
+//! The [VideoDetails] are also loaded from the API.
+
+use serde::Deserialize;
+use tf_core::{ErrorStore, Video};
+use tf_pt::{PTSubscription, PTVideo};
+
+use crate::details::{Chapter, VideoDetails};
+use crate::metadata::{self, VideoKind, VideoMetadata};
+
+/// The number of latest videos to request, as many as the RSS feed contains.
+const VIDEO_COUNT: &str = "20";
+
+#[derive(Deserialize)]
+struct VideoList {
+    data: Vec<ApiVideo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiVideo {
+    uuid: String,
+    #[serde(default)]
+    short_uuid: Option<String>,
+    name: String,
+    published_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    thumbnail_path: Option<String>,
+    #[serde(default)]
+    duration: i64,
+    #[serde(default)]
+    views: i64,
+    #[serde(default)]
+    is_live: bool,
+}
+
+impl ApiVideo {
+    /// The url of the video, in the same form as in the RSS feed. Depending on the version of the
+    /// instance, the url contains either the short uuid or the uuid.
+    fn url(&self, base_url: &str) -> String {
+        match &self.short_uuid {
+            Some(short_uuid) => format!("{}/w/{}", base_url, short_uuid),
+            None => format!("{}/videos/watch/{}", base_url, self.uuid),
+        }
+    }
+
+    fn video(&self, subscription: &PTSubscription) -> PTVideo {
+        let base_url = subscription.base_url();
+        let thumbnail_url = self
+            .thumbnail_path
+            .as_ref()
+            .map(|p| format!("{}{}", base_url, p))
+            .unwrap_or_default();
+        PTVideo::new(
+            self.url(&base_url),
+            self.name.clone(),
+            // The RSS feed contains the time in UTC as well.
+            self.published_at.naive_utc(),
+            subscription.clone(),
+            thumbnail_url,
+        )
+    }
+
+    fn metadata(&self) -> VideoMetadata {
+        let duration = Some(self.duration).filter(|d| d > &0);
+        VideoMetadata {
+            duration,
+            views: Some(self.views),
+            kind: if self.is_live {
+                VideoKind::Live
+            } else {
+                VideoKind::Video
+            },
+        }
+    }
+}
+
+async fn api_videos(
+    subscription: &PTSubscription,
+    client: &reqwest::Client,
+) -> crate::api::Result<Vec<ApiVideo>> {
+    let request = client
+        .get(format!(
+            "{}/api/v1/video-channels/{}/videos",
+            subscription.base_url(),
+            subscription.id()
+        ))
+        .query(&[("count", VIDEO_COUNT), ("sort", "-publishedAt")]);
+    let response = crate::http_cache::send(client, request).await?;
+    let list: VideoList = serde_json::from_slice(&response)?;
+    Ok(list.data)
+}
+
+/// Get the latest videos of the given channel and store their metadata.
+///
+/// If the API fails, the videos are taken from the RSS feed without metadata. Only failing to
+/// load the RSS feed is considered an error of the subscription.
+pub async fn channel_videos(
+    subscription: &PTSubscription,
+    errors: &ErrorStore,
+    client: &reqwest::Client,
+) -> Vec<PTVideo> {
+    match api_videos(subscription, client).await {
+        Ok(api_videos) => api_videos
+            .iter()
+            .map(|api_video| {
+                let video = api_video.video(subscription);
+                metadata::insert(video.url(), api_video.metadata());
+                video
+            })
+            .collect(),
+        Err(e) => {
+            log::warn!(
+                "Failed to load the videos of {} from the API, using the RSS feed: {}",
+                subscription,
+                e
+            );
+            crate::rss::videos(subscription, errors, client).await
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiVideoDetails {
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    published_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    views: i64,
+    #[serde(default)]
+    likes: i64,
+}
+
+#[derive(Deserialize)]
+struct ChapterList {
+    chapters: Vec<ApiChapter>,
+}
+
+#[derive(Deserialize)]
+struct ApiChapter {
+    timecode: i64,
+    title: String,
+}
+
+/// The API url of the video with the given url.
+pub fn api_url(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let id = url.path_segments()?.next_back()?;
+    Some(format!(
+        "{}/api/v1/videos/{}",
+        url.origin().ascii_serialization(),
+        id
+    ))
+}
+
+/// Get the details of the video with the given url.
+pub async fn video_details(url: &str) -> Option<VideoDetails> {
+    let client = reqwest::Client::new();
+    let api_url = api_url(url)?;
+    let chapters_url = format!("{}/chapters", api_url);
+    // Chapters are only supported since PeerTube 6.
+    let (details, chapters) = futures::join!(
+        crate::http_cache::get(&client, &api_url),
+        crate::http_cache::get(&client, &chapters_url)
+    );
+    let details: ApiVideoDetails = details
+        .map_err(|e| e.to_string())
+        .and_then(|d| serde_json::from_slice(&d).map_err(|e| e.to_string()))
+        .map_err(|e| log::error!("Failed to get the details of {}: {}", url, e))
+        .ok()?;
+    let chapters = chapters
+        .ok()
+        .and_then(|c| serde_json::from_slice::<ChapterList>(&c).ok())
+        .map(|c| c.chapters)
+        .unwrap_or_default();
+
+    Some(VideoDetails {
+        description: details.description.unwrap_or_default(),
+        tags: details.tags,
+        chapters: chapters
+            .into_iter()
+            .map(|c| Chapter {
+                start: c.timecode,
+                title: c.title,
+            })
+            .collect(),
+        uploaded: details
+            .published_at
+            .map(|d| d.with_timezone(&chrono::Local).naive_local()),
+        views: Some(details.views),
+        likes: Some(details.likes),
+    })
+}
+
+/// The highest resolution to play in the application, higher ones are hard to play on phones.
+const MAX_MEDIA_RESOLUTION: i64 = 720;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiVideoFiles {
+    #[serde(default)]
+    files: Vec<ApiFile>,
+    #[serde(default)]
+    streaming_playlists: Vec<ApiStreamingPlaylist>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiFile {
+    file_url: String,
+    resolution: ApiResolution,
+}
+
+/// The resolution of a file, 0 for audio only.
+#[derive(Deserialize)]
+struct ApiResolution {
+    id: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiStreamingPlaylist {
+    playlist_url: String,
+}
+
+/// The url of a media file of the video with the given url.
+///
+/// This is the file with the highest resolution up to [MAX_MEDIA_RESOLUTION], or else the lowest
+/// one. Videos which are only available as HLS return the url of the playlist instead.
+pub async fn media_url(url: &str) -> Option<String> {
+    let client = reqwest::Client::new();
+    let api_url = api_url(url)?;
+    let files: ApiVideoFiles = crate::http_cache::get(&client, &api_url)
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|d| serde_json::from_slice(&d).map_err(|e| e.to_string()))
+        .map_err(|e| log::error!("Failed to get the files of {}: {}", url, e))
+        .ok()?;
+    let videos = files.files.iter().filter(|f| f.resolution.id > 0);
+    videos
+        .clone()
+        .filter(|f| f.resolution.id <= MAX_MEDIA_RESOLUTION)
+        .max_by_key(|f| f.resolution.id)
+        .or_else(|| videos.min_by_key(|f| f.resolution.id))
+        .map(|f| f.file_url.clone())
+        .or_else(|| {
+            files
+                .streaming_playlists
+                .into_iter()
+                .next()
+                .map(|p| p.playlist_url)
+        })
+}
@@ -16,62 +16,132 @@
  * You should have received a copy of the GNU General Public License
  * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
  */
+use std::thread;
 
-use std::{
-    fmt::Display,
-    process::{Command, Stdio},
-    thread,
-};
+use crate::command::{self, Placeholders};
+use crate::mpv;
 
+/// Play the video described by the placeholders with the player command, starting at `start`
+/// seconds if given.
+///
+/// If the player is mpv, it is tracked using [mpv::watch](crate::mpv::watch) and `progress` is
+/// called with every change of the playback. `callback` is called once the player exited, after
+/// all changes were reported, with the error if the player could not be run or failed.
 pub fn play<
-    S: 'static + AsRef<str> + Display + std::convert::AsRef<std::ffi::OsStr> + std::marker::Send,
-    F: Fn() + std::marker::Send + 'static,
+    P: FnMut(mpv::Event) + std::marker::Send + 'static,
+    F: FnOnce(Result<(), command::Error>) + std::marker::Send + 'static,
 >(
-    url: S,
+    player: &str,
+    values: Placeholders,
+    start: Option<f64>,
+    progress: P,
     callback: F,
 ) {
-    log::debug!("Playing video with url: {}", url);
-    let player_str = std::env::var("PLAYER").unwrap_or("mpv --ytdl".to_string());
-    open_with(url, player_str, callback);
+    log::debug!("Playing video with url: {:?}", values.url);
+    let mut args = match command::build(player, &values) {
+        Ok(args) => args,
+        Err(e) => {
+            log::error!("Invalid player command: {}", e);
+            callback(Err(e));
+            return;
+        }
+    };
+    if let Some(start) = start.filter(|_| mpv::enabled(&args[0])) {
+        args.push(format!("--start={}", start));
+    }
+    open_tracked(args, progress, callback);
 }
 
-pub fn open_with<
-    S: 'static + AsRef<str> + Display + std::convert::AsRef<std::ffi::OsStr> + std::marker::Send,
-    F: Fn() + std::marker::Send + 'static,
+/// Run the player with the arguments, tracking it using [mpv::watch](crate::mpv::watch) if it is
+/// mpv.
+fn open_tracked<
+    P: FnMut(mpv::Event) + std::marker::Send + 'static,
+    F: FnOnce(Result<(), command::Error>) + std::marker::Send + 'static,
 >(
-    url: S,
-    command: String,
+    mut args: Vec<String>,
+    progress: P,
     callback: F,
 ) {
-    thread::spawn(move || {
-        let mut command_iter = command.split(" ");
-        let program = command_iter
-            .next()
-            .expect("The command should have a program");
-        let args: Vec<String> = command_iter.map(|s| s.to_string()).collect();
+    if !mpv::enabled(&args[0]) {
+        open_with(args, callback);
+        return;
+    }
 
-        let stdout = if log::log_enabled!(log::Level::Debug) {
-            Stdio::inherit()
-        } else {
-            Stdio::null()
-        };
+    let socket = mpv::socket_path();
+    args.push(mpv::argument(&socket));
+    let watcher = thread::spawn(move || {
+        if let Err(e) = mpv::watch(&socket, progress) {
+            log::warn!("Failed to track the playback of mpv: {}", e);
+        }
+        let _ = std::fs::remove_file(&socket);
+    });
+    open_with(args, move |result| {
+        // The watcher would only give up connecting after a while if mpv never started.
+        if !matches!(result, Err(command::Error::Run(..))) {
+            let _ = watcher.join();
+        }
+        callback(result);
+    });
+}
 
-        let stderr = if log::log_enabled!(log::Level::Error) {
-            Stdio::inherit()
-        } else {
-            Stdio::null()
-        };
+/// Play all given urls in one player as a m3u playlist, using the player command.
+///
+/// If the player is mpv, it is tracked using [mpv::watch](crate::mpv::watch) and `progress` is
+/// called with every change of the playback, including the entry being played. `callback` is
+/// called once the player exited.
+pub fn play_playlist<
+    P: FnMut(mpv::Event) + std::marker::Send + 'static,
+    F: FnOnce(Result<(), command::Error>) + std::marker::Send + 'static,
+>(
+    player: &str,
+    urls: Vec<String>,
+    progress: P,
+    callback: F,
+) {
+    log::debug!("Playing playlist with {} videos", urls.len());
+    let mut path = gtk::glib::user_cache_dir();
+    path.push("tubefeeder");
+    let playlist = format!("#EXTM3U\n{}\n", urls.join("\n"));
+    let result = std::fs::create_dir_all(&path).and_then(|_| {
+        path.push("queue.m3u");
+        std::fs::write(&path, playlist)
+    });
+    if let Err(e) = result {
+        log::error!("Failed to write the playlist to {}: {}", path.display(), e);
+        callback(Err(command::Error::Run(
+            path.to_string_lossy().into_owned(),
+            e,
+        )));
+        return;
+    }
+    let args = match command::build(
+        player,
+        &Placeholders::url(path.to_string_lossy().into_owned()),
+    ) {
+        Ok(args) => args,
+        Err(e) => {
+            log::error!("Invalid player command: {}", e);
+            callback(Err(e));
+            return;
+        }
+    };
 
-        let _ = Command::new(&program)
-            .args(args)
-            .arg(url)
-            .stdout(stdout)
-            .stderr(stderr)
-            .stdin(Stdio::null())
-            .spawn()
-            .unwrap()
-            .wait();
+    open_tracked(args, progress, callback);
+}
 
-        callback();
+/// Run the program with the arguments, the first argument being the program.
+///
+/// `callback` is called once the program exited, with the error if it could not be run or
+/// failed.
+pub fn open_with<F: FnOnce(Result<(), command::Error>) + std::marker::Send + 'static>(
+    args: Vec<String>,
+    callback: F,
+) {
+    thread::spawn(move || {
+        let result = command::run(&args, |_| ());
+        if let Err(e) = &result {
+            log::error!("Failed to run the player: {}", e);
+        }
+        callback(result);
     });
 }
@@ -0,0 +1,83 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Known players and downloaders, offered as presets if they are installed.
+
+use crate::command;
+
+/// A known program with the command to use it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preset {
+    pub name: String,
+    pub command: String,
+}
+
+impl Preset {
+    fn new(name: &str, command: String) -> Self {
+        Self {
+            name: name.to_owned(),
+            command,
+        }
+    }
+
+    /// The program run by the command.
+    pub fn program(&self) -> String {
+        command::parse(&self.command)
+            .ok()
+            .and_then(|args| args.into_iter().next())
+            .unwrap_or_default()
+    }
+
+    pub fn installed(&self) -> bool {
+        command::installed(&self.program())
+    }
+}
+
+/// The known players, by preference.
+pub fn players() -> Vec<Preset> {
+    vec![
+        Preset::new("mpv", "mpv --ytdl".to_owned()),
+        Preset::new("Celluloid", "celluloid --new-window".to_owned()),
+        Preset::new("VLC", "vlc --play-and-exit".to_owned()),
+        Preset::new("Videos", "totem".to_owned()),
+        Preset::new("Haruna", "haruna".to_owned()),
+    ]
+}
+
+/// The known downloaders, by preference.
+pub fn downloaders() -> Vec<Preset> {
+    let output = shell_words::quote(&crate::downloader::output_template()).into_owned();
+    vec![
+        Preset::new("yt-dlp", format!("yt-dlp --output {}", output)),
+        Preset::new("youtube-dl", format!("youtube-dl --output {}", output)),
+    ]
+}
+
+/// The presets whose programs are installed.
+pub fn installed(presets: Vec<Preset>) -> Vec<Preset> {
+    presets.into_iter().filter(Preset::installed).collect()
+}
+
+/// Whether the program of the command is installed, `true` if the command is invalid as this
+/// is reported otherwise.
+pub fn command_installed(command: &str) -> bool {
+    command::parse(command)
+        .map(|args| command::installed(&args[0]))
+        .unwrap_or(true)
+}
@@ -0,0 +1,178 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Named player profiles.
+//!
+//! Besides the player command in `PLAYER`, profiles with a name and their own command can be
+//! configured. They are stored as JSON in the `PLAYER_PROFILES` environment variable. The profile
+//! named in `PLAYER_PROFILE` is used to play videos instead of `PLAYER`, the others are offered
+//! as alternatives.
+//!
+//! Profiles may be restricted to some platforms, in which case they are only used for videos of
+//! these platforms. Videos of other platforms are played with `PLAYER` if the default profile is
+//! restricted.
+
+use serde::{Deserialize, Serialize};
+use tf_join::Platform;
+
+/// A named player command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub command: String,
+    /// The names of the platforms the profile is restricted to, empty for all platforms.
+    #[serde(default)]
+    pub platforms: Vec<String>,
+}
+
+impl Profile {
+    /// The name of the profile, the program of its command if it has no name.
+    pub fn label(&self) -> String {
+        if !self.name.is_empty() {
+            return self.name.clone();
+        }
+        crate::command::parse(&self.command)
+            .ok()
+            .and_then(|args| args.into_iter().next())
+            .map(|program| {
+                std::path::Path::new(&program)
+                    .file_name()
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .unwrap_or(program)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether the profile may be used for videos of the platform.
+    pub fn applies_to(&self, platform: Option<&str>) -> bool {
+        self.platforms.is_empty()
+            || platform
+                .map(|platform| self.platforms.iter().any(|p| p == platform))
+                .unwrap_or_default()
+    }
+}
+
+/// The GSettings-representation of a profile: name, command and platforms.
+pub type ProfileTuple = (String, String, Vec<String>);
+
+impl From<ProfileTuple> for Profile {
+    fn from((name, command, platforms): ProfileTuple) -> Self {
+        Self {
+            name,
+            command,
+            platforms,
+        }
+    }
+}
+
+impl From<Profile> for ProfileTuple {
+    fn from(profile: Profile) -> Self {
+        (profile.name, profile.command, profile.platforms)
+    }
+}
+
+/// The names of the platforms a profile can be restricted to.
+pub fn platforms() -> Vec<String> {
+    [Platform::Youtube, Platform::Peertube, Platform::Lbry]
+        .iter()
+        .map(|p| p.to_string())
+        .collect()
+}
+
+/// The configured player command, used if no profile applies.
+pub fn player() -> String {
+    crate::environment::var("PLAYER").unwrap_or("mpv --ytdl".to_string())
+}
+
+/// The configured profiles.
+pub fn configured() -> Vec<Profile> {
+    crate::environment::var("PLAYER_PROFILES")
+        .and_then(|p| serde_json::from_str(&p).ok())
+        .unwrap_or_default()
+}
+
+/// Set the configured profiles.
+pub fn set_configured(profiles: &[Profile]) {
+    crate::environment::set_var(
+        "PLAYER_PROFILES",
+        serde_json::to_string(profiles).unwrap_or_default(),
+    );
+}
+
+/// The name of the default profile, `None` if `PLAYER` is the default.
+pub fn default_name() -> Option<String> {
+    crate::environment::var("PLAYER_PROFILE").filter(|name| !name.is_empty())
+}
+
+/// Set the name of the default profile, `None` to use `PLAYER`.
+pub fn set_default_name(name: Option<&str>) {
+    crate::environment::set_var("PLAYER_PROFILE", name.unwrap_or_default());
+}
+
+/// The profile with the name.
+pub fn get(name: &str) -> Option<Profile> {
+    configured().into_iter().find(|p| p.name == name)
+}
+
+/// The command of the profile with the name, `PLAYER` for the empty name.
+pub fn command_of(name: &str) -> String {
+    if name.is_empty() {
+        return player();
+    }
+    get(name).map(|p| p.command).unwrap_or_else(player)
+}
+
+/// The default profile if it applies to the platform.
+fn default_profile(platform: Option<&str>) -> Option<Profile> {
+    default_name()
+        .and_then(|name| get(&name))
+        .filter(|p| p.applies_to(platform))
+}
+
+/// The profile to play videos of the platform with, a profile without a name for `PLAYER`.
+pub fn default(platform: Option<&str>) -> Profile {
+    default_profile(platform).unwrap_or_else(|| Profile {
+        name: String::new(),
+        command: player(),
+        platforms: vec![],
+    })
+}
+
+/// The command to play videos of the platform with.
+pub fn command(platform: Option<&str>) -> String {
+    default(platform).command
+}
+
+/// The profiles applying to the platform, except the one which plays its videos by default.
+///
+/// If a profile is the default, `PLAYER` is the first alternative as a profile without a name.
+pub fn alternatives(platform: Option<&str>) -> Vec<Profile> {
+    let default = default_profile(platform);
+    let player = default.as_ref().map(|_| Profile {
+        name: String::new(),
+        command: player(),
+        platforms: vec![],
+    });
+    player
+        .into_iter()
+        .chain(configured().into_iter().filter(|p| {
+            p.applies_to(platform) && Some(&p.name) != default.as_ref().map(|d| &d.name)
+        }))
+        .collect()
+}
@@ -0,0 +1,433 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! External providers.
+//!
+//! A provider is a executable inside of `$XDG_DATA_HOME/tubefeeder/providers/`. It is called with
+//! the id of a subscription as its only argument and has to print a JSON-array of videos to
+//! stdout, e.g.:
+//!
+//! ```json
+//! [
+//!   {
+//!     "title": "Some Video",
+//!     "url": "https://example.com/watch/1",
+//!     "thumbnail_url": "https://example.com/thumbnail/1.jpg",
+//!     "uploaded": "2022-01-31T12:00:00",
+//!     "author": "Some Channel",
+//!     "duration": 630,
+//!     "views": 1200,
+//!     "kind": "video"
+//!   }
+//! ]
+//! ```
+//!
+//! `thumbnail_url`, `author`, `duration` (in seconds), `views` and `kind` are optional. `uploaded`
+//! uses [tf_core::DATE_FORMAT]. `kind` is one of `video`, `live`, `upcoming` or `short`, `video`
+//! by default.
+
+use std::{
+    convert::TryFrom,
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+    process::Stdio,
+    sync::{Arc, Mutex, Weak},
+};
+
+use futures::{stream::FuturesUnordered, StreamExt};
+use regex::Regex;
+use serde::Deserialize;
+use tf_core::{ErrorStore, NetworkError, ParseError, DATE_FORMAT};
+use tf_filter::FilterGroup;
+use tf_join::AnyVideoFilter;
+use tf_observer::{Observable, Observer, ObserverList};
+
+use crate::metadata::VideoMetadata;
+use crate::scheduler::{Failure, Scheduler};
+
+/// The directory the provider executables are located in.
+pub fn providers_dir() -> PathBuf {
+    let mut path = gtk::glib::user_data_dir();
+    path.push("tubefeeder");
+    path.push("providers");
+    path
+}
+
+/// A executable providing videos.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provider {
+    name: String,
+    path: PathBuf,
+}
+
+impl Provider {
+    /// All executables found in the [providers_dir], sorted by name.
+    pub fn all() -> Vec<Self> {
+        let entries = match std::fs::read_dir(providers_dir()) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+
+        let mut providers: Vec<Self> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.metadata()
+                    .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                    .unwrap_or_default()
+            })
+            .map(|e| Self {
+                name: e.file_name().to_string_lossy().to_string(),
+                path: e.path(),
+            })
+            .collect();
+        providers.sort_by(|p1, p2| p1.name.cmp(&p2.name));
+        providers
+    }
+
+    /// The provider with the given name, if it is installed.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|p| p.name == name)
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// A subscription to a channel of a [Provider].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProviderSubscription {
+    provider: String,
+    id: String,
+}
+
+impl ProviderSubscription {
+    pub fn new<S1: AsRef<str>, S2: AsRef<str>>(provider: S1, id: S2) -> Self {
+        Self {
+            provider: provider.as_ref().to_owned(),
+            id: id.as_ref().to_owned(),
+        }
+    }
+
+    pub fn provider(&self) -> String {
+        self.provider.clone()
+    }
+
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    /// Call the provider and parse its output. Failures will be put into the given [ErrorStore].
+    pub async fn generate(&self, errors: &ErrorStore) -> Vec<ProviderVideo> {
+        log::debug!(
+            "Generating videos from provider {} with id {}",
+            self.provider,
+            self.id
+        );
+        let provider = match Provider::from_name(&self.provider) {
+            Some(provider) => provider,
+            None => {
+                log::error!("Provider {} is not installed", self.provider);
+                errors.add(NetworkError(format!("{} (provider not installed)", self)).into());
+                return vec![];
+            }
+        };
+
+        let output = tokio::process::Command::new(&provider.path)
+            .arg(&self.id)
+            .stdin(Stdio::null())
+            .output()
+            .await;
+
+        let output = match output {
+            Ok(output) if output.status.success() => output,
+            Ok(output) => {
+                log::error!(
+                    "Provider {} failed for {}: {}",
+                    self.provider,
+                    self.id,
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                errors.add(NetworkError(self.to_string()).into());
+                return vec![];
+            }
+            Err(e) => {
+                log::error!("Failed to run provider {}: {}", self.provider, e);
+                errors.add(NetworkError(self.to_string()).into());
+                return vec![];
+            }
+        };
+
+        let parsed: Result<Vec<ProviderVideoJson>, _> = serde_json::from_slice(&output.stdout);
+        match parsed {
+            Ok(videos) => videos
+                .into_iter()
+                .filter_map(|v| {
+                    let video = ProviderVideo::from_json(v, self);
+                    if video.is_none() {
+                        errors.add(ParseError(self.to_string()).into());
+                    }
+                    video
+                })
+                .collect(),
+            Err(e) => {
+                log::error!(
+                    "Failed to parse output of provider {}: {}",
+                    self.provider,
+                    e
+                );
+                errors.add(ParseError(self.to_string()).into());
+                vec![]
+            }
+        }
+    }
+}
+
+impl ProviderSubscription {
+    /// Generate the (filtered) videos using the [Scheduler].
+    pub async fn generate_scheduled(
+        &self,
+        errors: &ErrorStore,
+        filters: &ProviderVideoFilters,
+        scheduler: &Scheduler,
+    ) -> Result<Vec<ProviderVideo>, Failure<ProviderSubscription>> {
+        let mut videos = scheduler
+            .fetch(self.clone(), None, errors, |errors| async move {
+                self.generate(&errors).await
+            })
+            .await?;
+        videos.retain(|v| !filters.matches(v));
+        Ok(videos)
+    }
+}
+
+impl std::fmt::Display for ProviderSubscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl TryFrom<Vec<String>> for ProviderSubscription {
+    type Error = ();
+
+    fn try_from(strings: Vec<String>) -> Result<Self, Self::Error> {
+        if let (Some(provider), Some(id)) = (strings.first(), strings.get(1)) {
+            Ok(ProviderSubscription::new(provider, id))
+        } else {
+            Err(())
+        }
+    }
+}
+
+impl From<ProviderSubscription> for Vec<String> {
+    fn from(sub: ProviderSubscription) -> Self {
+        vec![sub.provider, sub.id]
+    }
+}
+
+#[derive(Deserialize)]
+struct ProviderVideoJson {
+    title: String,
+    url: String,
+    #[serde(default)]
+    thumbnail_url: Option<String>,
+    uploaded: String,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    duration: Option<i64>,
+    #[serde(default)]
+    views: Option<i64>,
+    #[serde(default)]
+    kind: Option<String>,
+}
+
+/// A video returned by a [Provider].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProviderVideo {
+    pub title: String,
+    pub url: String,
+    pub thumbnail_url: String,
+    pub uploaded: chrono::NaiveDateTime,
+    pub author: String,
+    pub metadata: VideoMetadata,
+    pub subscription: ProviderSubscription,
+}
+
+impl ProviderVideo {
+    fn from_json(json: ProviderVideoJson, subscription: &ProviderSubscription) -> Option<Self> {
+        let uploaded = chrono::NaiveDateTime::parse_from_str(&json.uploaded, DATE_FORMAT).ok()?;
+        Some(Self {
+            title: json.title,
+            url: json.url,
+            thumbnail_url: json.thumbnail_url.unwrap_or_default(),
+            uploaded,
+            author: json.author.unwrap_or_else(|| subscription.to_string()),
+            metadata: VideoMetadata {
+                duration: json.duration,
+                views: json.views,
+                kind: json.kind.and_then(|k| k.parse().ok()).unwrap_or_default(),
+            },
+            subscription: subscription.clone(),
+        })
+    }
+}
+
+/// The title and channel of [AnyVideoFilter]s, compiled once to match [ProviderVideo]s.
+pub struct ProviderVideoFilters(Vec<(Option<Regex>, Option<Regex>)>);
+
+impl ProviderVideoFilters {
+    pub fn new(filters: &Mutex<FilterGroup<AnyVideoFilter>>) -> Self {
+        let compile =
+            |r: Option<String>| r.map(|r| Regex::new(&r).expect("Regex of a filter to be valid"));
+        Self(
+            filters
+                .lock()
+                .expect("Filter Group to be lockable")
+                .iter()
+                .map(|f| (compile(f.title_str()), compile(f.subscription_str())))
+                .filter(|(title, author)| title.is_some() || author.is_some())
+                .collect(),
+        )
+    }
+
+    /// Whether the video matches the title and channel of any of the filters.
+    pub fn matches(&self, video: &ProviderVideo) -> bool {
+        self.0.iter().any(|(title, author)| {
+            title.as_ref().map_or(true, |r| r.is_match(&video.title))
+                && author.as_ref().map_or(true, |r| r.is_match(&video.author))
+        })
+    }
+}
+
+/// A event of the [ProviderSubscriptionList].
+#[derive(Clone)]
+pub enum ProviderSubscriptionEvent {
+    Add(ProviderSubscription),
+    Remove(ProviderSubscription),
+}
+
+/// The list of all [ProviderSubscription]s, similar to [tf_join::AnySubscriptionList].
+#[derive(Clone, Default)]
+pub struct ProviderSubscriptionList {
+    observers: ObserverList<ProviderSubscriptionEvent>,
+    subscriptions: Arc<Mutex<Vec<ProviderSubscription>>>,
+}
+
+impl ProviderSubscriptionList {
+    pub fn add(&self, subscription: ProviderSubscription) {
+        {
+            let mut subscriptions = self
+                .subscriptions
+                .lock()
+                .expect("Provider subscriptions to be lockable");
+            if subscriptions.contains(&subscription) {
+                return;
+            }
+            subscriptions.push(subscription.clone());
+        }
+        self.observers
+            .notify(ProviderSubscriptionEvent::Add(subscription));
+    }
+
+    pub fn remove(&self, subscription: ProviderSubscription) {
+        {
+            let mut subscriptions = self
+                .subscriptions
+                .lock()
+                .expect("Provider subscriptions to be lockable");
+            let len = subscriptions.len();
+            subscriptions.retain(|s| s != &subscription);
+            if subscriptions.len() == len {
+                return;
+            }
+        }
+        self.observers
+            .notify(ProviderSubscriptionEvent::Remove(subscription));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ProviderSubscription> {
+        self.subscriptions
+            .lock()
+            .expect("Provider subscriptions to be lockable")
+            .clone()
+            .into_iter()
+    }
+
+    /// Generate the videos of all subscriptions, newest first.
+    ///
+    /// Videos matching any of the given filters are removed.
+    pub async fn generate(
+        &self,
+        errors: &ErrorStore,
+        filters: Arc<Mutex<FilterGroup<AnyVideoFilter>>>,
+        scheduler: &Scheduler,
+    ) -> Vec<ProviderVideo> {
+        self.generate_progressive(errors, filters, scheduler, |_| {})
+            .await
+    }
+
+    /// Generate the videos of all subscriptions, newest first.
+    ///
+    /// `on_generated` is called with the result of every subscription as soon as it is generated.
+    pub async fn generate_progressive<F>(
+        &self,
+        errors: &ErrorStore,
+        filters: Arc<Mutex<FilterGroup<AnyVideoFilter>>>,
+        scheduler: &Scheduler,
+        on_generated: F,
+    ) -> Vec<ProviderVideo>
+    where
+        F: Fn(Result<Vec<ProviderVideo>, Failure<ProviderSubscription>>),
+    {
+        let subscriptions: Vec<ProviderSubscription> = self.iter().collect();
+        let filters = ProviderVideoFilters::new(&filters);
+        let mut results: FuturesUnordered<_> = subscriptions
+            .iter()
+            .map(|s| s.generate_scheduled(errors, &filters, scheduler))
+            .collect();
+
+        let mut videos = vec![];
+        while let Some(result) = results.next().await {
+            if let Ok(generated) = &result {
+                videos.extend(generated.iter().cloned());
+            }
+            on_generated(result);
+        }
+        videos.sort_by_cached_key(|v| v.uploaded);
+        videos.reverse();
+        videos
+    }
+}
+
+impl Observable<ProviderSubscriptionEvent> for ProviderSubscriptionList {
+    fn attach(
+        &mut self,
+        observer: Weak<Mutex<Box<dyn Observer<ProviderSubscriptionEvent> + Send>>>,
+    ) {
+        self.observers.attach(observer);
+    }
+
+    fn detach(
+        &mut self,
+        observer: Weak<Mutex<Box<dyn Observer<ProviderSubscriptionEvent> + Send>>>,
+    ) {
+        self.observers.detach(observer);
+    }
+}
@@ -0,0 +1,61 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Generation of videos from RSS feeds, used for PeerTube and LBRY.
+//!
+//! This replaces the generation of [tf_utils], which does not use the [crate::http_cache].
+
+use tf_core::{ErrorStore, NetworkError, ParseError, Subscription, Video};
+use tf_utils::rss::{FromItemAndSub, Rss, RssExtractor, WithName};
+
+async fn parse_rss_from_url(url: &str, client: &reqwest::Client) -> Result<Rss, tf_core::Error> {
+    let body = crate::http_cache::get(client, url).await.map_err(|e| {
+        log::error!("Error getting {}: {}", url, e);
+        tf_core::Error::from(NetworkError(url.to_owned()))
+    })?;
+    let body = String::from_utf8_lossy(&body)
+        .replace("media:", "media/")
+        .replace("itunes:", "itunes/");
+    quick_xml::de::from_str(&body).map_err(|e| {
+        log::error!("Error parsing {}: {}", url, e);
+        ParseError(url.to_owned()).into()
+    })
+}
+
+/// Get the videos from the RSS feed of the given subscription.
+pub async fn videos<S, V>(subscription: &S, errors: &ErrorStore, client: &reqwest::Client) -> Vec<V>
+where
+    S: Subscription<Video = V> + WithName + RssExtractor,
+    V: Video<Subscription = S> + FromItemAndSub<S>,
+{
+    match parse_rss_from_url(&subscription.feed_url(), client).await {
+        Ok(rss) => {
+            let name = rss.channel.itunes_author;
+            rss.channel
+                .items
+                .into_iter()
+                .map(|i| V::from_item_and_sub(i, subscription.with_name(&name)))
+                .collect()
+        }
+        Err(e) => {
+            errors.add(e);
+            vec![]
+        }
+    }
+}
@@ -0,0 +1,190 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Scheduling of the requests generating the feed.
+//!
+//! Instead of requesting all subscriptions at once, at most `FETCH_CONCURRENCY` subscriptions are
+//! fetched at the same time. Every fetch is cancelled after `FETCH_TIMEOUT` seconds and retried
+//! `FETCH_RETRIES` times with exponential backoff. Requests to the same host are delayed to be at
+//! least `FETCH_HOST_INTERVAL` milliseconds apart.
+//!
+//! All of those are [environment](crate::environment) variables, initialized from the settings.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tf_core::{ErrorStore, NetworkError};
+use tokio::sync::Semaphore;
+
+/// The delay before the first retry, doubled for every further retry.
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+
+/// A subscription which could not be fetched.
+#[derive(Debug, Clone)]
+pub struct Failure<S> {
+    pub subscription: S,
+    /// The errors of the last attempt.
+    pub errors: Vec<tf_core::Error>,
+}
+
+/// The configuration of the [Scheduler].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchSettings {
+    pub concurrency: usize,
+    pub timeout: Duration,
+    pub retries: u32,
+    pub host_interval: Duration,
+}
+
+impl Default for FetchSettings {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            timeout: Duration::from_secs(30),
+            retries: 2,
+            host_interval: Duration::from_millis(250),
+        }
+    }
+}
+
+fn env_number<T: std::str::FromStr>(env: &str) -> Option<T> {
+    crate::environment::var(env).and_then(|v| v.parse().ok())
+}
+
+impl FetchSettings {
+    /// The settings configured by the user.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            concurrency: env_number("FETCH_CONCURRENCY")
+                .filter(|c| c > &0)
+                .unwrap_or(default.concurrency),
+            timeout: env_number("FETCH_TIMEOUT")
+                .map(Duration::from_secs)
+                .unwrap_or(default.timeout),
+            retries: env_number("FETCH_RETRIES").unwrap_or(default.retries),
+            host_interval: env_number("FETCH_HOST_INTERVAL")
+                .map(Duration::from_millis)
+                .unwrap_or(default.host_interval),
+        }
+    }
+}
+
+/// Limits the fetches of subscriptions, see the [module documentation](self).
+pub struct Scheduler {
+    settings: FetchSettings,
+    permits: Semaphore,
+    /// The time the latest request to each host is scheduled at.
+    hosts: Mutex<HashMap<String, Instant>>,
+}
+
+impl Scheduler {
+    pub fn new(settings: FetchSettings) -> Self {
+        Self {
+            settings,
+            permits: Semaphore::new(settings.concurrency),
+            hosts: Default::default(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(FetchSettings::from_env())
+    }
+
+    /// Wait until a request to the host is allowed.
+    async fn wait_for_host(&self, host: &str) {
+        let wait = {
+            let mut hosts = self.hosts.lock().expect("Hosts to be lockable");
+            let now = Instant::now();
+            let scheduled = hosts
+                .get(host)
+                .map(|last| *last + self.settings.host_interval)
+                .filter(|next| next > &now)
+                .unwrap_or(now);
+            hosts.insert(host.to_owned(), scheduled);
+            scheduled - now
+        };
+        tokio::time::sleep(wait).await;
+    }
+
+    /// Fetch the videos of the subscription from the host.
+    ///
+    /// A attempt is considered failed if it timed out or returned no videos but errors. Only the
+    /// errors of the last attempt will be put into the [ErrorStore], such that every subscription
+    /// reports at most one outcome.
+    pub async fn fetch<S, T, F, Fut>(
+        &self,
+        subscription: S,
+        host: Option<String>,
+        errors: &ErrorStore,
+        fetch: F,
+    ) -> Result<Vec<T>, Failure<S>>
+    where
+        S: std::fmt::Display,
+        F: Fn(ErrorStore) -> Fut,
+        Fut: Future<Output = Vec<T>>,
+    {
+        let name = subscription.to_string();
+        let mut attempt = 0;
+        loop {
+            let attempt_errors = ErrorStore::new();
+            let result = {
+                let _permit = self
+                    .permits
+                    .acquire()
+                    .await
+                    .expect("Semaphore to not be closed");
+                if let Some(host) = &host {
+                    self.wait_for_host(host).await;
+                }
+                tokio::time::timeout(self.settings.timeout, fetch(attempt_errors.clone())).await
+            };
+
+            let failure: Vec<tf_core::Error> = match result {
+                Ok(videos) if !videos.is_empty() || attempt_errors.iter().next().is_none() => {
+                    attempt_errors.iter().for_each(|e| errors.add(e));
+                    log::debug!("Fetched {} after {} attempts", name, attempt + 1);
+                    return Ok(videos);
+                }
+                Ok(_) => attempt_errors.iter().collect(),
+                Err(_) => {
+                    log::warn!("Fetching {} timed out", name);
+                    vec![NetworkError(format!("{} (timed out)", name)).into()]
+                }
+            };
+
+            if attempt >= self.settings.retries {
+                log::error!("Fetching {} failed after {} attempts", name, attempt + 1);
+                failure.iter().for_each(|e| errors.add(e.clone()));
+                return Err(Failure {
+                    subscription,
+                    errors: failure,
+                });
+            }
+            let backoff = BACKOFF_BASE * 2u32.pow(attempt);
+            log::warn!("Fetching {} failed, retrying in {:?}", name, backoff);
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+}
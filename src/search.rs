@@ -0,0 +1,148 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Searching videos using a simple query syntax.
+//!
+//! A query consists of terms separated by whitespace. Terms can be quoted to contain whitespace
+//! and can be restricted to the author or platform of a video using `author:` or `platform:`,
+//! e.g. `author:"Some Channel" platform:peertube "some phrase"`. A video matches the query if it
+//! matches all terms, ignoring case.
+
+/// A single term of a [Query].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    /// Matches the title, author or platform.
+    Any(String),
+    Author(String),
+    Platform(String),
+}
+
+/// A parsed search query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+impl Query {
+    pub fn parse<S: AsRef<str>>(query: S) -> Self {
+        let terms = tokenize(query.as_ref())
+            .into_iter()
+            .map(|t| t.to_lowercase())
+            .map(|t| {
+                if let Some(author) = t.strip_prefix("author:") {
+                    Term::Author(author.to_owned())
+                } else if let Some(platform) = t.strip_prefix("platform:") {
+                    Term::Platform(platform.to_owned())
+                } else {
+                    Term::Any(t)
+                }
+            })
+            .filter(|t| match t {
+                Term::Any(s) | Term::Author(s) | Term::Platform(s) => !s.is_empty(),
+            })
+            .collect();
+        Self { terms }
+    }
+
+    /// Whether the query matches everything.
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, title: &str, author: &str, platform: &str) -> bool {
+        let title = title.to_lowercase();
+        let author = author.to_lowercase();
+        let platform = platform.to_lowercase();
+        self.terms.iter().all(|t| match t {
+            Term::Any(s) => title.contains(s) || author.contains(s) || platform.contains(s),
+            Term::Author(s) => author.contains(s),
+            Term::Platform(s) => platform.contains(s),
+        })
+    }
+}
+
+/// Split the query at whitespace not enclosed in quotes. The quotes are removed.
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_quoted_phrases() {
+        assert_eq!(
+            tokenize(r#"  some "quoted phrase"  author:"Some Channel" "#),
+            vec!["some", "quoted phrase", "author:Some Channel"]
+        );
+        // An unclosed quote extends to the end of the query.
+        assert_eq!(tokenize(r#"a "b c"#), vec!["a", "b c"]);
+        assert!(tokenize(r#"  "" "#).is_empty());
+    }
+
+    #[test]
+    fn parse_terms() {
+        assert_eq!(
+            Query::parse(r#"Rust author:"Some Channel" platform:PeerTube"#).terms,
+            vec![
+                Term::Any("rust".to_owned()),
+                Term::Author("some channel".to_owned()),
+                Term::Platform("peertube".to_owned()),
+            ]
+        );
+        // Prefixes without a value are ignored.
+        assert!(Query::parse("author: platform:").is_empty());
+        assert!(Query::parse("   ").is_empty());
+    }
+
+    #[test]
+    fn match_all_terms_ignoring_case() {
+        let query = Query::parse(r#"author:channel "a video""#);
+        assert!(query.matches("This is A Video", "Some Channel", "YouTube"));
+        assert!(!query.matches("This is A Video", "Someone", "YouTube"));
+        assert!(!query.matches("Another one", "Some Channel", "YouTube"));
+
+        let query = Query::parse("platform:peertube");
+        assert!(query.matches("Title", "Author", "Peertube"));
+        assert!(!query.matches("peertube", "peertube", "YouTube"));
+
+        // Unrestricted terms match any of the fields.
+        let query = Query::parse("lbry");
+        assert!(query.matches("Title", "Author", "LBRY"));
+        assert!(Query::default().matches("Title", "Author", "YouTube"));
+    }
+}
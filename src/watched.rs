@@ -0,0 +1,152 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The playback state of videos: the ones played until the end, stored one url per line, and
+//! where to resume the others. Also the videos which were in the feed at the last visit.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+static WATCHED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(load()));
+static RESUME: Lazy<Mutex<HashMap<String, ResumePosition>>> =
+    Lazy::new(|| Mutex::new(load_resume()));
+
+/// Where to continue playing a video.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResumePosition {
+    /// The position in seconds.
+    pub position: f64,
+    /// The duration of the video in seconds, if known.
+    pub duration: Option<f64>,
+}
+
+impl ResumePosition {
+    /// The played fraction of the video, between 0 and 1.
+    pub fn progress(&self) -> f64 {
+        match self.duration {
+            Some(duration) if duration > 0.0 => (self.position / duration).clamp(0.0, 1.0),
+            _ => 0.0,
+        }
+    }
+}
+
+fn data_path(file: &str) -> PathBuf {
+    let mut path = gtk::glib::user_data_dir();
+    path.push("tubefeeder");
+    path.push(file);
+    path
+}
+
+fn path() -> PathBuf {
+    data_path("watched.txt")
+}
+
+fn load() -> HashSet<String> {
+    std::fs::read_to_string(path())
+        .map(|content| content.lines().map(|l| l.to_owned()).collect())
+        .unwrap_or_default()
+}
+
+fn seen_path() -> PathBuf {
+    data_path("seen.txt")
+}
+
+fn load_resume() -> HashMap<String, ResumePosition> {
+    std::fs::read_to_string(data_path("resume.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Where to resume the video with the given url.
+pub fn resume_position(url: &str) -> Option<ResumePosition> {
+    RESUME
+        .lock()
+        .expect("Resume positions to be lockable")
+        .get(url)
+        .copied()
+}
+
+/// Remember where to resume the video with the given url, or forget it.
+pub fn set_resume_position(url: &str, position: Option<ResumePosition>) {
+    let mut resume = RESUME.lock().expect("Resume positions to be lockable");
+    let changed = match position {
+        Some(position) => resume.insert(url.to_owned(), position) != Some(position),
+        None => resume.remove(url).is_some(),
+    };
+    if !changed {
+        return;
+    }
+    let result = serde_json::to_string(&*resume)
+        .map_err(std::io::Error::from)
+        .and_then(|content| std::fs::write(data_path("resume.json"), content));
+    if let Err(e) = result {
+        log::warn!("Failed to save the resume position of {}: {}", url, e);
+    }
+}
+
+/// Whether the video with the given url was watched.
+pub fn is_watched(url: &str) -> bool {
+    WATCHED
+        .lock()
+        .expect("Watched videos to be lockable")
+        .contains(url)
+}
+
+/// Remember that the video with the given url was watched.
+pub fn set_watched(url: &str) {
+    set_resume_position(url, None);
+    let inserted = WATCHED
+        .lock()
+        .expect("Watched videos to be lockable")
+        .insert(url.to_owned());
+    if !inserted {
+        return;
+    }
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path())
+        .and_then(|mut file| writeln!(file, "{}", url));
+    if let Err(e) = result {
+        log::warn!("Failed to save the watched video {}: {}", url, e);
+    }
+}
+
+/// The urls of the videos which were in the feed at the last visit, `None` if they were never
+/// saved.
+pub fn seen() -> Option<HashSet<String>> {
+    std::fs::read_to_string(seen_path())
+        .ok()
+        .map(|content| content.lines().map(|l| l.to_owned()).collect())
+}
+
+/// Remember the urls of the videos in the feed at the current visit.
+pub fn set_seen(urls: &HashSet<String>) {
+    let content: String = urls.iter().map(|url| format!("{}\n", url)).collect();
+    if let Err(e) = std::fs::write(seen_path(), content) {
+        log::warn!("Failed to save the seen videos: {}", e);
+    }
+}
@@ -0,0 +1,399 @@
+/*
+ * Copyright 2021 - 2022 Julian Schmidhuber <github@schmiddi.anonaddy.com>
+ *
+ * This file is part of Pipeline.
+ *
+ * Pipeline is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Pipeline is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Pipeline.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The backends used to access YouTube.
+//!
+//! The preferred backend is taken from the `YOUTUBE_BACKEND` environment variable. Each request is
+//! tried on the instances of the preferred backend first (see [crate::instances]), afterwards on
+//! the instances of the other backend.
+
+use std::str::FromStr;
+
+use piped::{PipedClient, RelatedStream};
+use serde::Deserialize;
+use tf_core::ErrorStore;
+use tf_yt::{YTSubscription, YTVideo};
+
+use crate::details::{Chapter, VideoDetails};
+use crate::instances;
+use crate::invidious::InvidiousClient;
+use crate::metadata::{self, VideoKind, VideoMetadata};
+
+const YOUTUBE_URL: &str = "https://www.youtube.com";
+
+/// Videos of at most this duration in seconds are considered to be shorts.
+const SHORT_DURATION: i64 = 60;
+
+/// A API giving access to YouTube.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Piped,
+    Invidious,
+}
+
+impl Backend {
+    pub fn values() -> Vec<Self> {
+        vec![Backend::Piped, Backend::Invidious]
+    }
+
+    /// The backend configured by the user.
+    pub fn preferred() -> Self {
+        crate::environment::var("YOUTUBE_BACKEND")
+            .and_then(|b| b.parse().ok())
+            .unwrap_or(Backend::Piped)
+    }
+
+    /// The backends in the order they should be tried.
+    pub fn ordered() -> Vec<Self> {
+        let preferred = Self::preferred();
+        let mut backends = vec![preferred];
+        backends.extend(Self::values().into_iter().filter(|b| b != &preferred));
+        backends
+    }
+}
+
+impl FromStr for Backend {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "piped" => Ok(Backend::Piped),
+            "invidious" => Ok(Backend::Invidious),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Backend::Piped => write!(f, "piped"),
+            Backend::Invidious => write!(f, "invidious"),
+        }
+    }
+}
+
+/// All instances to try, in order.
+pub fn ordered_instances() -> Vec<(Backend, String)> {
+    Backend::ordered()
+        .into_iter()
+        .flat_map(|b| instances::ordered(b).into_iter().map(move |i| (b, i)))
+        .collect()
+}
+
+fn piped_to_tubefeeder_error(error: &piped::Error) -> tf_core::Error {
+    match error {
+        piped::Error::Network(e) => tf_core::NetworkError(e.to_string()).into(),
+        piped::Error::ParseResponse(e) => tf_core::ParseError(e.to_string()).into(),
+        piped::Error::Parseurl(e) => tf_core::ParseError(e.to_string()).into(),
+    }
+}
+
+/// The part of a video of the Piped API used for the [VideoDetails].
+///
+/// Not using [piped::VideoInfo], as it does not contain e.g. the chapters and fails to parse
+/// livestreams.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PipedStream {
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    upload_date: Option<String>,
+    #[serde(default)]
+    views: i64,
+    #[serde(default)]
+    likes: i64,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    chapters: Vec<PipedChapter>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PipedChapter {
+    title: String,
+    start: i64,
+}
+
+impl From<PipedStream> for VideoDetails {
+    fn from(v: PipedStream) -> Self {
+        VideoDetails {
+            description: crate::details::html_to_text(&v.description),
+            tags: v.tags,
+            chapters: v
+                .chapters
+                .into_iter()
+                .map(|c| Chapter {
+                    start: c.start,
+                    title: c.title,
+                })
+                .collect(),
+            // Depending on the version, either a date or a date and time.
+            uploaded: v.upload_date.and_then(|d| {
+                chrono::NaiveDate::parse_from_str(d.get(..10)?, "%Y-%m-%d")
+                    .ok()?
+                    .and_hms_opt(0, 0, 0)
+            }),
+            views: known_views(v.views),
+            likes: Some(v.likes).filter(|l| l >= &0),
+        }
+    }
+}
+
+/// The id of the YouTube video with the given url.
+pub fn video_id(url: &str) -> Option<String> {
+    reqwest::Url::parse(url)
+        .ok()?
+        .query_pairs()
+        .find(|(k, _)| k == "v")
+        .map(|(_, v)| v.to_string())
+}
+
+/// Search for a channel with the given name and return the first result.
+pub async fn search_channel<S: AsRef<str>>(query: S) -> Option<YTSubscription> {
+    let client = reqwest::Client::new();
+    for (backend, instance) in ordered_instances() {
+        let result = match backend {
+            Backend::Piped => PipedClient::new(&client, &instance)
+                .search_channel(query.as_ref())
+                .await
+                .map(|s| s.items.first().map(|i| i.into()))
+                .map_err(|e| e.to_string()),
+            Backend::Invidious => InvidiousClient::new(&client, &instance)
+                .search_channel(query.as_ref())
+                .await
+                .map(|items| {
+                    items
+                        .first()
+                        .map(|i| YTSubscription::new_with_name(&i.author_id, &i.author))
+                })
+                .map_err(|e| e.to_string()),
+        };
+        match result {
+            Ok(subscription) => {
+                instances::report_success(&instance);
+                return subscription;
+            }
+            Err(e) => {
+                log::error!("Failed to search channel on {}: {}", instance, e);
+                instances::report_failure(&instance);
+            }
+        }
+    }
+    None
+}
+
+/// Get the latest videos of the given channel.
+///
+/// Only the error of the last tried instance will be put into the [ErrorStore].
+pub async fn channel_videos(
+    subscription: &YTSubscription,
+    errors: &ErrorStore,
+    client: &reqwest::Client,
+) -> Vec<YTVideo> {
+    log::debug!("Generating YT videos from channel {}", subscription);
+    let mut last_error = None;
+    for (backend, instance) in ordered_instances() {
+        let result = match backend {
+            Backend::Piped => piped_channel_videos(subscription, &instance, client).await,
+            Backend::Invidious => invidious_channel_videos(subscription, &instance, client).await,
+        };
+        match result {
+            Ok(videos) => {
+                instances::report_success(&instance);
+                return videos;
+            }
+            Err(e) => {
+                log::error!(
+                    "Error generating youtube videos from subscription {:?} using {}: {:?}",
+                    subscription,
+                    instance,
+                    e
+                );
+                instances::report_failure(&instance);
+                last_error = Some(e);
+            }
+        }
+    }
+    if let Some(e) = last_error {
+        errors.add(e);
+    }
+    vec![]
+}
+
+/// The kind of a video with the given duration in seconds, as the backends do not report whether
+/// a video is a short.
+fn kind_from_duration(duration: Option<i64>) -> VideoKind {
+    match duration {
+        Some(d) if d <= SHORT_DURATION => VideoKind::Short,
+        _ => VideoKind::Video,
+    }
+}
+
+/// The duration, if it is known. The backends use non-positive durations for unknown durations.
+fn known_duration(duration: i64) -> Option<i64> {
+    Some(duration).filter(|d| d > &0)
+}
+
+/// The view count, if it is known. Upcoming videos and some livestreams report negative views.
+fn known_views(views: i64) -> Option<i64> {
+    Some(views).filter(|v| v >= &0)
+}
+
+fn piped_video(video: &RelatedStream, subscription: YTSubscription) -> YTVideo {
+    let url = format!("{}/{}", YOUTUBE_URL, video.url);
+    let duration = known_duration(video.duration.into());
+    // Piped does not differentiate livestreams from videos, except by the duration and upload
+    // time.
+    let kind = if video.uploaded > chrono::Utc::now().timestamp_millis() {
+        VideoKind::Upcoming
+    } else if video.duration < 0 {
+        VideoKind::Live
+    } else {
+        kind_from_duration(duration)
+    };
+    metadata::insert(
+        &url,
+        VideoMetadata {
+            duration,
+            views: known_views(video.views),
+            kind,
+        },
+    );
+    YTVideo::new(
+        &url,
+        &video.title,
+        chrono::NaiveDateTime::from_timestamp_opt(video.uploaded / 1000, 0).unwrap_or_default(),
+        subscription,
+        &video.thumbnail,
+    )
+}
+
+async fn piped_channel_videos(
+    subscription: &YTSubscription,
+    instance: &str,
+    client: &reqwest::Client,
+) -> Result<Vec<YTVideo>, tf_core::Error> {
+    // Not using the [PipedClient], such that the response can be cached.
+    let response = crate::http_cache::get(
+        client,
+        format!(
+            "{}/channel/{}",
+            instance.trim_end_matches('/'),
+            subscription.id()
+        ),
+    )
+    .await
+    .map_err(|e| piped_to_tubefeeder_error(&e.into()))?;
+    let channel: piped::Channel =
+        serde_json::from_slice(&response).map_err(|e| piped_to_tubefeeder_error(&e.into()))?;
+    let subscription = YTSubscription::new_with_name(&subscription.id(), &channel.name);
+    Ok(channel
+        .related_streams
+        .iter()
+        .map(|v| piped_video(v, subscription.clone()))
+        .collect())
+}
+
+async fn invidious_channel_videos(
+    subscription: &YTSubscription,
+    instance: &str,
+    client: &reqwest::Client,
+) -> Result<Vec<YTVideo>, tf_core::Error> {
+    let invidious = InvidiousClient::new(client, instance);
+    let videos = invidious
+        .channel_videos(subscription.id())
+        .await
+        .map_err(|e| tf_core::Error::from(&e))?;
+    Ok(videos
+        .into_iter()
+        .map(|v| {
+            // Matches the urls generated from piped, otherwise the videos would not be
+            // recognized as the same.
+            let url = format!("{}//watch?v={}", YOUTUBE_URL, v.video_id);
+            let duration = known_duration(v.length_seconds);
+            let kind = if v.is_upcoming {
+                VideoKind::Upcoming
+            } else if v.live_now {
+                VideoKind::Live
+            } else {
+                kind_from_duration(duration)
+            };
+            metadata::insert(
+                &url,
+                VideoMetadata {
+                    duration,
+                    views: known_views(v.view_count),
+                    kind,
+                },
+            );
+            YTVideo::new(
+                &url,
+                &v.title,
+                chrono::NaiveDateTime::from_timestamp_opt(v.published, 0).unwrap_or_default(),
+                YTSubscription::new_with_name(&v.author_id, &v.author),
+                &invidious.thumbnail_url(&v.video_thumbnails),
+            )
+        })
+        .collect())
+}
+
+/// Get the details of the video with the given id.
+pub async fn video_info<S: AsRef<str>>(id: S) -> Option<VideoDetails> {
+    let client = reqwest::Client::new();
+    for (backend, instance) in ordered_instances() {
+        let result = match backend {
+            Backend::Piped => crate::http_cache::get(
+                &client,
+                format!("{}/streams/{}", instance.trim_end_matches('/'), id.as_ref()),
+            )
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| {
+                serde_json::from_slice::<PipedStream>(&r)
+                    .map(VideoDetails::from)
+                    .map_err(|e| e.to_string())
+            }),
+            Backend::Invidious => InvidiousClient::new(&client, &instance)
+                .video_from_id(id.as_ref())
+                .await
+                .map(|v| VideoDetails {
+                    description: v.description,
+                    tags: v.keywords,
+                    chapters: vec![],
+                    uploaded: chrono::NaiveDateTime::from_timestamp_opt(v.published, 0),
+                    views: known_views(v.view_count),
+                    likes: Some(v.like_count).filter(|l| l >= &0),
+                })
+                .map_err(|e| e.to_string()),
+        };
+        match result {
+            Ok(details) => {
+                instances::report_success(&instance);
+                return Some(details);
+            }
+            Err(e) => {
+                log::error!("Failed to get video info from {}: {}", instance, e);
+                instances::report_failure(&instance);
+            }
+        }
+    }
+    None
+}